@@ -49,40 +49,91 @@ fn setup(mut commands: Commands, mut config: ResMut<TextEditConfig>) {
             ..default()
         })
         .with_children(|parent| {
+            // The background is set on a parent node, so the selection highlight is visible behind the text.
+            // The parent also clips the text when it is scrolled.
             parent
                 .spawn((
-                    // Mark text is editable
-                    TextEditable {
-                        placeholder: String::from("Input your text here"),
-                        max_length: 255,
-                        ..default()
-                    },
-                    TextEditFocus, // Mark text is focused
                     Node {
                         height: Val::Px(64.),
                         width: Val::Percent(80.),
                         margin: UiRect::bottom(Val::Px(10.)),
+                        overflow: Overflow::clip(),
                         ..default()
                     },
                     BackgroundColor::from(ZINC_800),
                 ))
-                .observe(get_result);
+                .with_children(|parent| {
+                    parent
+                        .spawn((
+                            // Mark text is editable
+                            TextEditable {
+                                placeholder: String::from("Input your text here"),
+                                max_length: 255,
+                                ..default()
+                            },
+                            TextEditFocus, // Mark text is focused
+                            Node {
+                                width: Val::Percent(100.),
+                                ..default()
+                            },
+                        ))
+                        .observe(get_result);
+                });
 
             parent
                 .spawn((
-                    TextEditable {
-                        placeholder: String::from("Input your text here"),
-                        filter_in: vec!["[0-9]".to_string()], // Only accept number
+                    Node {
+                        height: Val::Px(64.),
+                        width: Val::Percent(80.),
+                        overflow: Overflow::clip(),
                         ..default()
                     },
+                    BackgroundColor::from(ZINC_800),
+                ))
+                .with_children(|parent| {
+                    parent
+                        .spawn((
+                            TextEditable {
+                                placeholder: String::from("Input your text here"),
+                                filter_in: vec!["[0-9]".to_string()], // Only accept number
+                                ..default()
+                            },
+                            Node {
+                                width: Val::Percent(100.),
+                                ..default()
+                            },
+                        ))
+                        .observe(get_result);
+                });
+
+            // Text area, scrolled vertically by its parent
+            parent
+                .spawn((
                     Node {
-                        height: Val::Px(64.),
                         width: Val::Percent(80.),
+                        margin: UiRect::top(Val::Px(10.)),
+                        overflow: Overflow::scroll_y(),
                         ..default()
                     },
                     BackgroundColor::from(ZINC_800),
                 ))
-                .observe(get_result);
+                .with_children(|parent| {
+                    parent
+                        .spawn((
+                            TextEditable {
+                                placeholder: String::from("Input multiple lines here, Ctrl+Enter to finish"),
+                                multiline: true,
+                                max_visible_lines: 5,
+                                max_length: 0,
+                                ..default()
+                            },
+                            Node {
+                                width: Val::Percent(100.),
+                                ..default()
+                            },
+                        ))
+                        .observe(get_result);
+                });
 
             parent.spawn((Result, Text::new("")));
         });
@@ -0,0 +1,266 @@
+//! Drive a `TextEditable` from code with `EntityCommands`.
+//!
+//! These commands go through the same path as user input: focus changes commit the text and send
+//! `TextFocusChanged`, and value changes are recorded in the history and send `TextChanged` with cause
+//! `TextChangeCause::Programmatic`.
+//! ```rust
+//! use bevy::prelude::*;
+//! use bevy_text_edit::commands::TextEditCommandsExt;
+//!
+//! fn edit_name(mut commands: Commands, name: Single<Entity, With<Name>>) {
+//!     commands.entity(*name).set_text_value("Player").focus_text().select_all();
+//! }
+//! ```
+
+use crate::display::TextMaskReveal;
+use crate::filter::{
+    PatternFilter,
+    TextInputFilters,
+};
+use crate::history::TextEditHistory;
+use crate::{
+    clamp_positions,
+    insert_input,
+    record_change,
+    send_input_rejected,
+    unfocus_text_box,
+    FocusedTextQuery,
+    TextChangeCause,
+    TextChanged,
+    TextEditBuffer,
+    TextEditConfig,
+    TextEditEndReason,
+    TextEditFocus,
+    TextEditSnapshot,
+    TextEditable,
+    TextEdited,
+    TextFocusChanged,
+};
+use bevy::ecs::system::{
+    EntityCommands,
+    SystemInput,
+};
+#[cfg(feature = "log")]
+use bevy::log::error;
+use bevy::prelude::{
+    Commands,
+    Entity,
+    EntityWorldMut,
+    GlobalTransform,
+    In,
+    IntoSystem,
+    MessageWriter,
+    Query,
+    Res,
+    Text,
+    Without,
+};
+
+/// Commands to edit a `TextEditable` from code.
+pub trait TextEditCommandsExt {
+    /// Focus the text, committing the other focused texts, and show the virtual keyboard.
+    /// A disabled text can't be focused.
+    fn focus_text(&mut self) -> &mut Self;
+
+    /// Commit the text and remove its focus, like a click elsewhere. See `TextEditConfig::blur_policy`.
+    fn blur_text(&mut self) -> &mut Self;
+
+    /// Replace the value and move the cursor to the end.
+    fn set_text_value(&mut self, value: impl Into<String>) -> &mut Self;
+
+    /// Insert text at the cursor, replacing the selection. It is filtered and limited like typed text.
+    fn insert_text_at_cursor(&mut self, text: impl Into<String>) -> &mut Self;
+
+    /// Select the whole value.
+    fn select_all(&mut self) -> &mut Self;
+}
+
+impl TextEditCommandsExt for EntityCommands<'_> {
+    fn focus_text(&mut self) -> &mut Self {
+        self.queue(|entity: EntityWorldMut| {
+            let e = entity.id();
+            run_command(entity, focus_text, e);
+        })
+    }
+
+    fn blur_text(&mut self) -> &mut Self {
+        self.queue(|entity: EntityWorldMut| {
+            let e = entity.id();
+            run_command(entity, blur_text, e);
+        })
+    }
+
+    fn set_text_value(&mut self, value: impl Into<String>) -> &mut Self {
+        let value = value.into();
+        self.queue(move |entity: EntityWorldMut| {
+            let e = entity.id();
+            run_command(entity, set_text_value, (e, value));
+        })
+    }
+
+    fn insert_text_at_cursor(&mut self, text: impl Into<String>) -> &mut Self {
+        let text = text.into();
+        self.queue(move |entity: EntityWorldMut| {
+            let e = entity.id();
+            run_command(entity, insert_text_at_cursor, (e, text));
+        })
+    }
+
+    fn select_all(&mut self) -> &mut Self {
+        self.queue(|mut entity: EntityWorldMut| {
+            if let Some(mut buffer) = entity.get_mut::<TextEditBuffer>() {
+                buffer.select_all();
+            }
+        })
+    }
+}
+
+fn run_command<I, M, S>(entity: EntityWorldMut, system: S, input: I::Inner<'_>)
+where
+    I: SystemInput + 'static,
+    S: IntoSystem<I, (), M> + 'static,
+{
+    if let Err(_e) = entity.into_world_mut().run_system_cached_with(system, input) {
+        #[cfg(feature = "log")]
+        error!("Failed to run text command: {}", _e);
+    }
+}
+
+fn focus_text(
+    In(e): In<Entity>,
+    mut commands: Commands,
+    mut focusing_texts: FocusedTextQuery,
+    texts: Query<(&TextEditable, &GlobalTransform)>,
+    mut text_edited_event: MessageWriter<TextEdited>,
+    mut text_changed_event: MessageWriter<TextChanged>,
+    mut focus_event: MessageWriter<TextFocusChanged>,
+    config: Res<TextEditConfig>,
+) {
+    let Ok((text_editable, global_transform)) = texts.get(e) else {
+        return;
+    };
+    if text_editable.disabled {
+        return;
+    }
+
+    let is_focused = focusing_texts.contains(e);
+    unfocus_text_box(
+        &mut commands,
+        &mut focusing_texts,
+        |focusing_e| focusing_e != e,
+        TextEditEndReason::Blur,
+        config.blur_policy,
+        &mut text_edited_event,
+        &mut text_changed_event,
+    );
+    if !is_focused {
+        commands.entity(e).insert(TextEditFocus);
+    }
+    focus_event.write(if text_editable.read_only {
+        TextFocusChanged::Hide
+    } else {
+        TextFocusChanged::Show(global_transform.translation().y)
+    });
+}
+
+fn blur_text(
+    In(e): In<Entity>,
+    mut commands: Commands,
+    mut focusing_texts: FocusedTextQuery,
+    mut text_edited_event: MessageWriter<TextEdited>,
+    mut text_changed_event: MessageWriter<TextChanged>,
+    mut focus_event: MessageWriter<TextFocusChanged>,
+    config: Res<TextEditConfig>,
+) {
+    if !focusing_texts.contains(e) {
+        return;
+    }
+
+    unfocus_text_box(
+        &mut commands,
+        &mut focusing_texts,
+        |focusing_e| focusing_e == e,
+        TextEditEndReason::Blur,
+        config.blur_policy,
+        &mut text_edited_event,
+        &mut text_changed_event,
+    );
+    focus_event.write(TextFocusChanged::Hide);
+}
+
+fn set_text_value(
+    In((e, value)): In<(Entity, String)>,
+    mut commands: Commands,
+    mut texts: Query<(
+        &mut TextEditBuffer,
+        &mut TextEditHistory,
+        &mut TextEditSnapshot,
+        &mut TextMaskReveal,
+    )>,
+    mut uninitialized_texts: Query<(&mut Text, Option<&mut TextEditBuffer>), Without<TextEditSnapshot>>,
+    mut text_changed_event: MessageWriter<TextChanged>,
+) {
+    // The buffer is initialized from `Text`, or from the buffer spawned with it, on the next update
+    if let Ok((mut text, buffer)) = uninitialized_texts.get_mut(e) {
+        match buffer {
+            Some(mut buffer) => buffer.set_value(value),
+            None => **text = value,
+        }
+        return;
+    }
+    let Ok((mut buffer, mut history, mut snapshot, mut reveal)) = texts.get_mut(e) else {
+        return;
+    };
+
+    let before = buffer.clone();
+    buffer.set_value(value);
+    record_change(
+        &mut commands,
+        &mut text_changed_event,
+        e,
+        before,
+        &buffer,
+        &mut history,
+        &mut snapshot,
+        &mut reveal,
+        TextChangeCause::Programmatic,
+    );
+}
+
+fn insert_text_at_cursor(
+    In((e, text)): In<(Entity, String)>,
+    mut commands: Commands,
+    mut texts: Query<(
+        &mut TextEditBuffer,
+        &mut TextEditHistory,
+        &mut TextEditSnapshot,
+        &mut TextMaskReveal,
+        &TextEditable,
+        &PatternFilter,
+        Option<&TextInputFilters>,
+    )>,
+    mut text_changed_event: MessageWriter<TextChanged>,
+) {
+    let Ok((mut buffer, mut history, mut snapshot, mut reveal, text_editable, pattern_filter, filters)) =
+        texts.get_mut(e)
+    else {
+        return;
+    };
+
+    clamp_positions(&mut buffer);
+    let before = buffer.clone();
+    if let Some((text, limit)) = insert_input(&mut buffer, &text, text_editable, pattern_filter, filters) {
+        send_input_rejected(&mut commands, e, text, limit);
+    }
+    record_change(
+        &mut commands,
+        &mut text_changed_event,
+        e,
+        before,
+        &buffer,
+        &mut history,
+        &mut snapshot,
+        &mut reveal,
+        TextChangeCause::Programmatic,
+    );
+}
@@ -0,0 +1,157 @@
+//! Text displayed by a `TextEditable`, which differs from its value while IME composes or when it is masked.
+//!
+//! Positions of the buffer are byte positions in the value. They are mapped to byte positions in the displayed text
+//! to be found in its layout, and back for pointer hits.
+
+use crate::ime::TextPreedit;
+use crate::{
+    TextEditBuffer,
+    TextEditConfig,
+    TextEditFocus,
+    TextEditable,
+};
+use bevy::prelude::{
+    Component,
+    Has,
+    Query,
+    Res,
+    Time,
+};
+use bevy::time::Stopwatch;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The last typed grapheme of a masked text, displayed for `TextEditConfig::mask_reveal_duration`.
+#[derive(Component, Default)]
+pub(crate) struct TextMaskReveal {
+    pub range: Option<Range<usize>>,
+    pub stopwatch: Stopwatch,
+}
+
+pub(crate) struct TextDisplay {
+    pub text: String,
+    /// Byte position of the text cursor. `None` if the IME hides it.
+    pub cursor: Option<usize>,
+    /// Byte range of the text composed by IME.
+    pub preedit: Option<Range<usize>>,
+    /// Byte range of the selection. It is replaced by the preedit text while composing.
+    pub selection: Option<Range<usize>>,
+    /// Grapheme boundaries of the value, as byte positions in the value and in the displayed text.
+    boundaries: Vec<(usize, usize)>,
+}
+
+impl TextDisplay {
+    pub fn new(
+        buffer: &TextEditBuffer,
+        preedit: &TextPreedit,
+        text_editable: &TextEditable,
+        reveal: &TextMaskReveal,
+    ) -> Self {
+        let mask = text_editable.mask;
+        let revealed = reveal.range.as_ref();
+        // The committed text replaces the selection, or is inserted at cursor.
+        let replaced = match buffer.selection() {
+            Some(range) if preedit.is_composing() => range,
+            _ => buffer.cursor..buffer.cursor,
+        };
+
+        let mut display = Self {
+            text: String::with_capacity(buffer.value.len()),
+            cursor: None,
+            preedit: None,
+            selection: None,
+            boundaries: Vec::new(),
+        };
+        display.push_value(&buffer.value, 0..replaced.start, mask, revealed);
+        let preedit_start = display.text.len();
+        display.push_masked(&preedit.text, mask);
+        display.push_value(&buffer.value, replaced.end..buffer.value.len(), mask, revealed);
+
+        if preedit.is_composing() {
+            display.preedit = Some(preedit_start..preedit_start + masked_len(&preedit.text, mask));
+            display.cursor = preedit
+                .cursor
+                .and_then(|(_, end)| preedit.text.get(..end))
+                .map(|before| preedit_start + masked_len(before, mask));
+        } else {
+            display.cursor = Some(display.to_display(buffer.cursor));
+            display.selection = buffer
+                .selection()
+                .map(|range| display.to_display(range.start)..display.to_display(range.end));
+        }
+
+        display
+    }
+
+    /// Byte position to keep in view: the cursor, or the end of the preedit text if the cursor is hidden.
+    pub fn view_position(&self) -> usize {
+        self.cursor
+            .or(self.preedit.as_ref().map(|range| range.end))
+            .unwrap_or_default()
+    }
+
+    /// Map byte position `pos` of the value to the displayed text.
+    pub fn to_display(&self, pos: usize) -> usize {
+        self.boundaries
+            .iter()
+            .rev()
+            .find(|(value_pos, _)| *value_pos <= pos)
+            .map(|(_, display_pos)| *display_pos)
+            .unwrap_or_default()
+    }
+
+    /// Map byte position `pos` of the displayed text to the value.
+    pub fn to_value(&self, pos: usize) -> usize {
+        self.boundaries
+            .iter()
+            .rev()
+            .find(|(_, display_pos)| *display_pos <= pos)
+            .map(|(value_pos, _)| *value_pos)
+            .unwrap_or_default()
+    }
+
+    fn push_value(&mut self, value: &str, range: Range<usize>, mask: Option<char>, revealed: Option<&Range<usize>>) {
+        for (i, grapheme) in value[range.clone()].grapheme_indices(true) {
+            let pos = range.start + i;
+            self.boundaries.push((pos, self.text.len()));
+            match mask {
+                Some(mask) if revealed != Some(&(pos..pos + grapheme.len())) => self.text.push(mask),
+                _ => self.text.push_str(grapheme),
+            }
+        }
+        self.boundaries.push((range.end, self.text.len()));
+    }
+
+    fn push_masked(&mut self, text: &str, mask: Option<char>) {
+        match mask {
+            Some(mask) => self.text.extend(text.graphemes(true).map(|_| mask)),
+            None => self.text.push_str(text),
+        }
+    }
+}
+
+/// Length of `text` once masked.
+fn masked_len(text: &str, mask: Option<char>) -> usize {
+    match mask {
+        Some(mask) => text.graphemes(true).count() * mask.len_utf8(),
+        None => text.len(),
+    }
+}
+
+/// Mask the revealed grapheme again when its time is over, or when its text loses focus.
+pub(crate) fn hide_revealed_char(
+    mut query: Query<(&mut TextMaskReveal, Has<TextEditFocus>)>,
+    time: Res<Time>,
+    config: Res<TextEditConfig>,
+) {
+    for (mut reveal, is_focused) in query.iter_mut() {
+        if reveal.range.is_none() {
+            continue;
+        }
+
+        reveal.stopwatch.tick(time.delta());
+        if reveal.stopwatch.elapsed_secs() >= config.mask_reveal_duration || !is_focused {
+            reveal.range = None;
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::{
+    TextEditBuffer,
     TextEditable,
     TextEdited,
 };
@@ -177,16 +178,16 @@ pub fn spawn_number_input_text(
 
 fn change_value(
     trigger: On<TextEdited>,
-    mut query: Query<(&mut Text, &NumberInput)>,
+    mut query: Query<(&mut TextEditBuffer, &NumberInput)>,
     parent_query: Query<&ChildOf>,
     commands: Commands,
 ) {
     let e = trigger.entity;
     let edited_text = trigger.text.clone();
-    if let Ok((mut text, setting)) = query.get_mut(e) {
+    if let Ok((mut buffer, setting)) = query.get_mut(e) {
         if let Ok(num) = edited_text.parse::<i64>() {
             let new_num = max(min(setting.max, num), setting.min);
-            **text = new_num.to_string();
+            buffer.set_value(new_num.to_string());
 
             number_input_notify(commands, parent_query, e, new_num);
         }
@@ -195,16 +196,16 @@ fn change_value(
 
 fn increase(
     trigger: On<Pointer<Click>>,
-    mut text_query: Query<(&mut Text, &NumberInput)>,
+    mut text_query: Query<(&mut TextEditBuffer, &NumberInput)>,
     button_query: Query<&NumberButton>,
     parent_query: Query<&ChildOf>,
     commands: Commands,
 ) {
     if let Ok(NumberButton(Some(e))) = button_query.get(trigger.entity) {
-        if let Ok((mut text, setting)) = text_query.get_mut(*e) {
-            if let Ok(num) = text.parse::<i64>() {
+        if let Ok((mut buffer, setting)) = text_query.get_mut(*e) {
+            if let Ok(num) = buffer.value.parse::<i64>() {
                 let new_num = min(setting.max, num + 1);
-                **text = new_num.to_string();
+                buffer.set_value(new_num.to_string());
 
                 number_input_notify(commands, parent_query, *e, new_num);
             }
@@ -214,16 +215,16 @@ fn increase(
 
 fn reduce(
     trigger: On<Pointer<Click>>,
-    mut text_query: Query<(&mut Text, &NumberInput)>,
+    mut text_query: Query<(&mut TextEditBuffer, &NumberInput)>,
     button_query: Query<&NumberButton>,
     parent_query: Query<&ChildOf>,
     commands: Commands,
 ) {
     if let Ok(NumberButton(Some(e))) = button_query.get(trigger.entity) {
-        if let Ok((mut text, setting)) = text_query.get_mut(*e) {
-            if let Ok(num) = text.parse::<i64>() {
+        if let Ok((mut buffer, setting)) = text_query.get_mut(*e) {
+            if let Ok(num) = buffer.value.parse::<i64>() {
                 let new_num = max(setting.min, num - 1);
-                **text = new_num.to_string();
+                buffer.set_value(new_num.to_string());
 
                 number_input_notify(commands, parent_query, *e, new_num);
             }
@@ -0,0 +1,199 @@
+//! Filters of the text entered into a `TextEditable`.
+//!
+//! `TextEditable::filter_out` and `TextEditable::filter_in` are applied by the built-in `PatternFilter`. Custom
+//! filters can be added with the component `TextInputFilters`:
+//! ```rust
+//! use bevy::prelude::*;
+//! use bevy_text_edit::filter::{
+//!     FilterResult,
+//!     InputFilter,
+//!     TextInputFilters,
+//! };
+//! use bevy_text_edit::TextEditable;
+//!
+//! /// Don't allow two spaces in a row.
+//! struct NoRepeatedSpaces;
+//!
+//! impl InputFilter for NoRepeatedSpaces {
+//!     fn filter(&self, value: &str, cursor: usize, insertion: &str) -> FilterResult {
+//!         if insertion.starts_with(' ') && value[..cursor].ends_with(' ') {
+//!             FilterResult::Reject
+//!         } else {
+//!             FilterResult::Accept
+//!         }
+//!     }
+//! }
+//!
+//! fn setup(mut commands: Commands) {
+//!     commands.spawn((
+//!         TextEditable::default(),
+//!         TextInputFilters::new()
+//!             .with(NoRepeatedSpaces)
+//!             // Closures are filters too
+//!             .with(|_value: &str, _cursor: usize, insertion: &str| {
+//!                 FilterResult::Transform(insertion.to_uppercase())
+//!             }),
+//!         Text::new("Input Text 1"),
+//!     ));
+//! }
+//! ```
+
+use crate::TextEditable;
+#[cfg(feature = "log")]
+use bevy::log::warn;
+use bevy::prelude::{
+    Changed,
+    Component,
+    Query,
+};
+use regex_lite::Regex;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Decision of an `InputFilter` on an insertion.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FilterResult {
+    Accept,
+    Reject,
+    /// Insert this text instead.
+    Transform(String),
+}
+
+/// Filter of the text inserted into a `TextEditable` by typing, pasting or IME.
+pub trait InputFilter: Send + Sync + 'static {
+    /// `value` is the current text, without the selection which will be replaced. `cursor` is the byte position
+    /// where `insertion` will be inserted.
+    fn filter(&self, value: &str, cursor: usize, insertion: &str) -> FilterResult;
+}
+
+impl<F> InputFilter for F
+where
+    F: Fn(&str, usize, &str) -> FilterResult + Send + Sync + 'static,
+{
+    fn filter(&self, value: &str, cursor: usize, insertion: &str) -> FilterResult {
+        self(value, cursor, insertion)
+    }
+}
+
+/// Custom filters of a `TextEditable`. They are applied in order, after `filter_out` and `filter_in`.
+#[derive(Component, Default)]
+pub struct TextInputFilters(pub Vec<Box<dyn InputFilter>>);
+
+impl TextInputFilters {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with(mut self, filter: impl InputFilter) -> Self {
+        self.0.push(Box::new(filter));
+        self
+    }
+}
+
+/// A pattern of `filter_in` or `filter_out`.
+enum Pattern {
+    Regex(Regex),
+    /// Invalid regex, matched as literal text.
+    Literal(String),
+}
+
+impl Pattern {
+    fn new(pattern: &str) -> Self {
+        match Regex::new(pattern) {
+            Ok(re) => Self::Regex(re),
+            Err(_e) => {
+                #[cfg(feature = "log")]
+                warn!(
+                    "Invalid filter pattern \"{}\", it is matched as literal text: {}",
+                    pattern, _e
+                );
+                Self::Literal(pattern.to_string())
+            }
+        }
+    }
+
+    fn is_match(&self, key: &str) -> bool {
+        match self {
+            Self::Regex(re) => re.is_match(key),
+            Self::Literal(pattern) => pattern == key,
+        }
+    }
+}
+
+/// Filter each grapheme with regex patterns. Graphemes which are not allowed are removed from the insertion.
+///
+/// It is inserted automatically with the compiled `filter_out` and `filter_in` of a `TextEditable`, and compiled again
+/// when the lists are changed.
+#[derive(Component)]
+pub struct PatternFilter {
+    sources: (Vec<String>, Vec<String>),
+    filter_out: Vec<Pattern>,
+    filter_in: Vec<Pattern>,
+}
+
+impl PatternFilter {
+    /// Graphemes matching `filter_out` are ignored. If `filter_in` is not empty, only graphemes matching it are allowed.
+    pub fn new(filter_out: &[String], filter_in: &[String]) -> Self {
+        Self {
+            sources: (filter_out.to_vec(), filter_in.to_vec()),
+            filter_out: filter_out.iter().map(|pattern| Pattern::new(pattern)).collect(),
+            filter_in: filter_in.iter().map(|pattern| Pattern::new(pattern)).collect(),
+        }
+    }
+
+    /// Return true if `key` is not allowed to be entered.
+    pub fn is_ignored(&self, key: &str) -> bool {
+        if self.filter_out.iter().any(|pattern| pattern.is_match(key)) {
+            return true;
+        }
+
+        !self.filter_in.is_empty() && !self.filter_in.iter().any(|pattern| pattern.is_match(key))
+    }
+}
+
+impl InputFilter for PatternFilter {
+    fn filter(&self, _value: &str, _cursor: usize, insertion: &str) -> FilterResult {
+        let allowed: String = insertion.graphemes(true).filter(|g| !self.is_ignored(g)).collect();
+        if allowed.len() == insertion.len() {
+            FilterResult::Accept
+        } else if allowed.is_empty() {
+            FilterResult::Reject
+        } else {
+            FilterResult::Transform(allowed)
+        }
+    }
+}
+
+/// Run `insertion` at byte `cursor` of `value` through the pattern filter then the custom filters.
+/// Return the text to insert, `None` if rejected.
+pub(crate) fn filter_insertion(
+    value: &str,
+    cursor: usize,
+    insertion: &str,
+    pattern_filter: &PatternFilter,
+    filters: Option<&TextInputFilters>,
+) -> Option<String> {
+    let custom_filters = filters
+        .into_iter()
+        .flat_map(|filters| filters.0.iter().map(|filter| filter.as_ref()));
+    let mut text = insertion.to_string();
+    for filter in std::iter::once(pattern_filter as &dyn InputFilter).chain(custom_filters) {
+        match filter.filter(value, cursor, &text) {
+            FilterResult::Accept => {}
+            FilterResult::Reject => return None,
+            FilterResult::Transform(transformed) => text = transformed,
+        }
+        if text.is_empty() {
+            return None;
+        }
+    }
+
+    Some(text)
+}
+
+pub(crate) fn update_pattern_filter(mut query: Query<(&TextEditable, &mut PatternFilter), Changed<TextEditable>>) {
+    for (text_editable, mut filter) in query.iter_mut() {
+        if filter.sources.0 != text_editable.filter_out || filter.sources.1 != text_editable.filter_in {
+            *filter = PatternFilter::new(&text_editable.filter_out, &text_editable.filter_in);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use crate::{
+    TextChangeCause,
+    TextEditBuffer,
+};
+use bevy::prelude::Component;
+
+const DEFAULT_MAX_DEPTH: usize = 100;
+
+/// Undo/redo history of a `TextEditable`.
+///
+/// Consecutive typed characters are merged into one undo step.
+/// It can be used directly to undo from your own UI:
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_text_edit::history::TextEditHistory;
+/// use bevy_text_edit::TextEditBuffer;
+///
+/// fn undo_button(mut query: Query<(&mut TextEditHistory, &mut TextEditBuffer)>) {
+///     for (mut history, mut buffer) in query.iter_mut() {
+///         history.undo(&mut buffer);
+///     }
+/// }
+/// ```
+#[derive(Component)]
+pub struct TextEditHistory {
+    /// Maximum number of undo steps. Default is 100. 0 means unlimited.
+    pub max_depth: usize,
+
+    undo_stack: Vec<TextEditBuffer>,
+    redo_stack: Vec<TextEditBuffer>,
+    last_cause: Option<TextChangeCause>,
+}
+
+impl Default for TextEditHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_DEPTH)
+    }
+}
+
+impl TextEditHistory {
+    pub fn new(max_depth: usize) -> Self {
+        Self {
+            max_depth,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            last_cause: None,
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Restore the buffer to the state before the last edit. Return false if there is nothing to undo.
+    pub fn undo(&mut self, buffer: &mut TextEditBuffer) -> bool {
+        let Some(prev) = self.undo_stack.pop() else {
+            return false;
+        };
+
+        self.redo_stack.push(std::mem::replace(buffer, prev));
+        self.last_cause = None;
+        true
+    }
+
+    /// Reapply the last undone edit. Return false if there is nothing to redo.
+    pub fn redo(&mut self, buffer: &mut TextEditBuffer) -> bool {
+        let Some(next) = self.redo_stack.pop() else {
+            return false;
+        };
+
+        self.undo_stack.push(std::mem::replace(buffer, next));
+        self.last_cause = None;
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.last_cause = None;
+    }
+
+    /// Save the buffer state before an edit.
+    pub(crate) fn push(&mut self, before: TextEditBuffer, cause: TextChangeCause) {
+        self.redo_stack.clear();
+        if cause == TextChangeCause::Typed && self.last_cause == Some(TextChangeCause::Typed) {
+            return;
+        }
+
+        self.undo_stack.push(before);
+        if self.max_depth > 0 && self.undo_stack.len() > self.max_depth {
+            self.undo_stack.remove(0);
+        }
+        self.last_cause = Some(cause);
+    }
+
+    /// Next edit will be a new undo step.
+    pub(crate) fn break_merge(&mut self) {
+        self.last_cause = None;
+    }
+}
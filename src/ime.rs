@@ -0,0 +1,123 @@
+//! IME composition of the focused `TextEditable`.
+//!
+//! The text being composed is displayed in place of the selection until it is committed, then it goes through the
+//! same insertion as typed text. The text composed in a masked text is masked too.
+
+use crate::display::{
+    TextDisplay,
+    TextMaskReveal,
+};
+use crate::layout::{
+    cursor_rect,
+    text_rows,
+};
+use crate::{
+    TextEditBuffer,
+    TextEditFocus,
+    TextEditable,
+};
+use bevy::math::Vec2;
+use bevy::prelude::{
+    Component,
+    DetectChanges,
+    Entity,
+    Query,
+    Ref,
+    Window,
+    With,
+    Without,
+};
+use bevy::text::{
+    ComputedTextBlock,
+    TextLayoutInfo,
+};
+use bevy::ui::{
+    ComputedNode,
+    UiGlobalTransform,
+};
+use bevy::window::PrimaryWindow;
+
+/// Text composed by the IME, not committed to the buffer yet.
+#[derive(Component, Default)]
+pub(crate) struct TextPreedit {
+    pub text: String,
+    /// Byte range of the IME cursor in `text`. `None` hides the cursor.
+    pub cursor: Option<(usize, usize)>,
+}
+
+impl TextPreedit {
+    pub fn is_composing(&self) -> bool {
+        !self.text.is_empty()
+    }
+}
+
+/// Enable IME only while a text which is not read-only is focused, and place its candidate window under the text cursor.
+pub(crate) fn update_ime(
+    mut windows: Query<&mut Window, With<PrimaryWindow>>,
+    focused_texts: Query<(
+        Entity,
+        Ref<TextEditBuffer>,
+        Ref<TextPreedit>,
+        Ref<TextEditable>,
+        Ref<TextMaskReveal>,
+        Ref<TextEditFocus>,
+    )>,
+    layouts: Query<(
+        &ComputedTextBlock,
+        Ref<TextLayoutInfo>,
+        Ref<ComputedNode>,
+        Ref<UiGlobalTransform>,
+    )>,
+    mut unfocused_texts: Query<&mut TextPreedit, Without<TextEditFocus>>,
+) {
+    // The composition is dropped with the focus
+    for mut preedit in unfocused_texts.iter_mut() {
+        if preedit.is_composing() {
+            *preedit = TextPreedit::default();
+        }
+    }
+
+    let Ok(mut window) = windows.single_mut() else {
+        return;
+    };
+
+    let focused = focused_texts
+        .iter()
+        .find(|(_, _, _, text_editable, ..)| !text_editable.read_only);
+    let is_enabled = window.ime_enabled;
+    if window.ime_enabled != focused.is_some() {
+        window.ime_enabled = focused.is_some();
+    }
+
+    let Some((e, buffer, preedit, text_editable, reveal, focus)) = focused else {
+        return;
+    };
+    let Ok((block, layout, computed_node, transform)) = layouts.get(e) else {
+        return;
+    };
+
+    // The position only moves with the cursor and the text node
+    let is_moved = !is_enabled
+        || focus.is_added()
+        || buffer.is_changed()
+        || preedit.is_changed()
+        || text_editable.is_changed()
+        || reveal.is_changed()
+        || layout.is_changed()
+        || computed_node.is_changed()
+        || transform.is_changed();
+    if !is_moved {
+        return;
+    }
+    let display = TextDisplay::new(&buffer, &preedit, &text_editable, &reveal);
+    let rows = text_rows(block, &display.text);
+    let Some(rect) = cursor_rect(&rows, display.view_position()) else {
+        return;
+    };
+
+    let text_min = transform.translation - 0.5 * computed_node.size() + computed_node.content_inset().min_inset;
+    let ime_position = (text_min + Vec2::new(rect.min.x, rect.max.y)) / window.scale_factor();
+    if window.ime_position != ime_position {
+        window.ime_position = ime_position;
+    }
+}
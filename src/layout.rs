@@ -0,0 +1,300 @@
+//! Map byte positions of a text to its computed glyph layout.
+//!
+//! All positions are in physical pixels, relative to the top-left corner of the text node.
+
+use bevy::math::{
+    Rect,
+    Vec2,
+};
+use bevy::text::ComputedTextBlock;
+use std::ops::Range;
+
+/// One glyph of a [`TextRow`].
+pub(crate) struct RowGlyph {
+    /// Byte range of the glyph in the text.
+    pub start: usize,
+    pub end: usize,
+    pub left: f32,
+    pub right: f32,
+}
+
+/// One visual row of the text. A line of text is split into several rows when it is wrapped.
+pub(crate) struct TextRow {
+    /// Byte range of the row in the text, without the line break.
+    pub start: usize,
+    pub end: usize,
+    pub top: f32,
+    pub height: f32,
+    pub right: f32,
+    pub glyphs: Vec<RowGlyph>,
+    /// This row is the last one of its line.
+    pub line_end: bool,
+}
+
+/// One row laid out by the text layout. Byte ranges are in the whole text.
+struct LayoutRow {
+    start: usize,
+    top: f32,
+    height: f32,
+    glyphs: Vec<RowGlyph>,
+}
+
+/// Split the laid out glyphs of `text` into visual rows.
+///
+/// Glyphs are placed by their advance in the layout, not by the bounds of their bitmap, so spaces and glyphs
+/// overhanging their neighbors are hit and highlighted like they are laid out.
+pub(crate) fn text_rows(block: &ComputedTextBlock, text: &str) -> Vec<TextRow> {
+    let layout = block.buffer();
+    let layout_rows = layout.lines().map(|line| {
+        let metrics = line.metrics();
+        let mut x = metrics.offset;
+        let mut glyphs = Vec::new();
+        for run in line.runs() {
+            for cluster in run.visual_clusters() {
+                let left = x;
+                x += cluster.advance();
+                if !cluster.is_hard_line_break() {
+                    let range = cluster.text_range();
+                    glyphs.push(RowGlyph {
+                        start: range.start,
+                        end: range.end,
+                        left,
+                        right: x,
+                    });
+                }
+            }
+        }
+        LayoutRow {
+            start: line.text_range().start,
+            top: metrics.block_min_coord,
+            height: metrics.block_max_coord - metrics.block_min_coord,
+            glyphs,
+        }
+    });
+    let default_height = layout
+        .lines()
+        .next()
+        .map(|line| line.metrics().line_height)
+        .unwrap_or_default();
+
+    build_rows(layout_rows, text, default_height)
+}
+
+fn build_rows(layout_rows: impl IntoIterator<Item = LayoutRow>, text: &str, default_height: f32) -> Vec<TextRow> {
+    let mut lines = Vec::new();
+    let mut line_start = 0;
+    for (i, _) in text.match_indices('\n') {
+        lines.push(line_start..i);
+        line_start = i + 1;
+    }
+    lines.push(line_start..text.len());
+
+    let mut rows: Vec<TextRow> = Vec::new();
+    let mut layout_rows = layout_rows.into_iter().peekable();
+    let mut top = 0.;
+    for line in &lines {
+        let first_row = rows.len();
+        while let Some(layout_row) = layout_rows.next_if(|row| row.start <= line.end) {
+            // A stale layout may not match the text
+            let glyphs: Vec<RowGlyph> = layout_row
+                .glyphs
+                .into_iter()
+                .map(|glyph| RowGlyph {
+                    start: glyph.start.clamp(line.start, line.end),
+                    end: glyph.end.clamp(line.start, line.end),
+                    ..glyph
+                })
+                .collect();
+            let row = TextRow {
+                start: layout_row.start.max(line.start),
+                end: line.end,
+                top: layout_row.top,
+                height: layout_row.height,
+                right: glyphs.last().map(|g| g.right).unwrap_or_default(),
+                glyphs,
+                line_end: false,
+            };
+            top = row.top + row.height;
+            rows.push(row);
+        }
+
+        if rows.len() == first_row {
+            rows.push(TextRow {
+                start: line.start,
+                end: line.end,
+                top,
+                height: default_height,
+                right: 0.,
+                glyphs: Vec::new(),
+                line_end: false,
+            });
+            top += default_height;
+        }
+
+        // A wrapped row ends where the next one starts.
+        for i in first_row..rows.len() - 1 {
+            rows[i].end = rows[i + 1].start;
+        }
+        if let Some(row) = rows.last_mut() {
+            row.line_end = true;
+        }
+    }
+
+    rows
+}
+
+/// Rect of the text cursor at byte position `pos`. Its width is the advance of the glyph after cursor, 0 at the row end.
+pub(crate) fn cursor_rect(rows: &[TextRow], pos: usize) -> Option<Rect> {
+    let row = rows
+        .iter()
+        .find(|row| row.start <= pos && (pos < row.end || (pos == row.end && row.line_end)))
+        .or(rows.last())?;
+    let (x, width) = match row.glyphs.iter().position(|g| g.end > pos) {
+        Some(i) => {
+            let glyph = &row.glyphs[i];
+            let right = row.glyphs.get(i + 1).map(|next| next.left).unwrap_or(glyph.right);
+            (glyph.left, right - glyph.left)
+        }
+        None => (row.right, 0.),
+    };
+
+    Some(Rect::new(x, row.top, x + width, row.top + row.height))
+}
+
+/// Rects covering the byte range `range`, one per row.
+pub(crate) fn selection_rects(rows: &[TextRow], range: Range<usize>) -> Vec<Rect> {
+    let mut rects = Vec::new();
+    for row in rows {
+        let start = range.start.max(row.start);
+        let end = range.end.min(row.end);
+        // A selected line break is shown as a narrow rect at the end of its line
+        let line_break = row.line_end && range.start <= row.end && range.end > row.end;
+        if start > end || (start == end && !line_break) {
+            continue;
+        }
+
+        let left = x_at(row, start);
+        let mut right = x_at(row, end);
+        if line_break {
+            right += row.height / 4.;
+        }
+        rects.push(Rect::new(left, row.top, right, row.top + row.height));
+    }
+
+    rects
+}
+
+/// Horizontal position of byte `pos` in the row.
+fn x_at(row: &TextRow, pos: usize) -> f32 {
+    row.glyphs
+        .iter()
+        .find(|g| g.end > pos)
+        .map(|g| g.left)
+        .unwrap_or(row.right)
+}
+
+/// Byte position of the glyph boundary closest to `point`.
+pub(crate) fn hit_position(rows: &[TextRow], point: Vec2) -> usize {
+    let Some(row) = rows.iter().find(|row| point.y < row.top + row.height).or(rows.last()) else {
+        return 0;
+    };
+
+    if let Some(glyph) = row.glyphs.iter().find(|g| point.x < (g.left + g.right) / 2.) {
+        return glyph.start;
+    }
+
+    // The end of a wrapped row is displayed at the start of the next row, stay before its last glyph instead.
+    if row.line_end {
+        row.end
+    } else {
+        row.glyphs.last().map(|g| g.start).unwrap_or(row.start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Row of 10px wide glyphs, one per byte of `range`.
+    fn layout_row(top: f32, range: Range<usize>) -> LayoutRow {
+        LayoutRow {
+            start: range.start,
+            top,
+            height: 20.,
+            glyphs: range
+                .enumerate()
+                .map(|(i, start)| RowGlyph {
+                    start,
+                    end: start + 1,
+                    left: i as f32 * 10.,
+                    right: (i + 1) as f32 * 10.,
+                })
+                .collect(),
+        }
+    }
+
+    /// "ab cd" wrapped after "ab ", an empty line, then "ef".
+    fn wrapped_rows() -> Vec<TextRow> {
+        let layout_rows = [
+            layout_row(0., 0..3),
+            layout_row(20., 3..5),
+            layout_row(40., 6..6),
+            layout_row(60., 7..9),
+        ];
+        build_rows(layout_rows, "ab cd\n\nef", 20.)
+    }
+
+    #[test]
+    fn rows() {
+        let rows = wrapped_rows();
+        let ranges: Vec<_> = rows.iter().map(|row| (row.start, row.end, row.line_end)).collect();
+        assert_eq!(ranges, [(0, 3, false), (3, 5, true), (6, 6, true), (7, 9, true)]);
+        assert_eq!(rows[3].glyphs[1].start, 8);
+        assert_eq!(rows[3].right, 20.);
+
+        // Lines missing from the layout are stacked below the last row
+        let rows = build_rows([layout_row(0., 0..2)], "ab\n", 20.);
+        assert_eq!(rows.len(), 2);
+        assert_eq!((rows[1].start, rows[1].top, rows[1].height), (3, 20., 20.));
+    }
+
+    #[test]
+    fn cursor() {
+        let rows = wrapped_rows();
+        assert_eq!(cursor_rect(&rows, 0), Some(Rect::new(0., 0., 10., 20.)));
+        // The wrap position is displayed at the start of the next row
+        assert_eq!(cursor_rect(&rows, 3), Some(Rect::new(0., 20., 10., 40.)));
+        assert_eq!(cursor_rect(&rows, 5), Some(Rect::new(20., 20., 20., 40.)));
+        assert_eq!(cursor_rect(&rows, 6), Some(Rect::new(0., 40., 0., 60.)));
+
+        let rows = build_rows([], "", 20.);
+        assert_eq!(cursor_rect(&rows, 0), Some(Rect::new(0., 0., 0., 20.)));
+    }
+
+    #[test]
+    fn hit() {
+        let rows = wrapped_rows();
+        assert_eq!(hit_position(&rows, Vec2::new(4., 10.)), 0);
+        assert_eq!(hit_position(&rows, Vec2::new(6., 10.)), 1);
+        // Past the end of a wrapped row stays before its last glyph
+        assert_eq!(hit_position(&rows, Vec2::new(100., 10.)), 2);
+        assert_eq!(hit_position(&rows, Vec2::new(100., 30.)), 5);
+        assert_eq!(hit_position(&rows, Vec2::new(5., 50.)), 6);
+        assert_eq!(hit_position(&rows, Vec2::new(100., 100.)), 9);
+    }
+
+    #[test]
+    fn selection() {
+        let rows = wrapped_rows();
+        assert_eq!(
+            selection_rects(&rows, 1..7),
+            [
+                Rect::new(10., 0., 30., 20.),
+                Rect::new(0., 20., 25., 40.),
+                Rect::new(0., 40., 5., 60.),
+            ]
+        );
+        assert_eq!(selection_rects(&rows, 7..9), [Rect::new(0., 60., 20., 80.)]);
+        assert!(selection_rects(&rows, 4..4).is_empty());
+    }
+}
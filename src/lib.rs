@@ -49,7 +49,9 @@
 //! }
 //! ```
 //!
-//! Only text that is focused by clicking gets keyboard input.
+//! Only text that is focused by clicking, by Tab, or by `commands::TextEditCommandsExt::focus_text` gets keyboard input.
+//!
+//! Set `multiline` to make a text area: Enter inserts a new line and `Ctrl+Enter` commits the text.
 //!
 //!
 //! It is also possible to limit which characters are allowed to enter through `filter_in` and `filter_out` attribute (regex is supported):
@@ -69,9 +71,11 @@
 //! }
 //! ```
 //!
+//! Any other rule can be written in Rust with the trait `filter::InputFilter` and component `filter::TextInputFilters`.
+//!
 //! ### Get text
 //!
-//! The edited text can be retrieved from event `TextEdited`.
+//! The edited text can be retrieved from event `TextEdited`, or at any time from component `TextEditBuffer`.
 //! ```rust
 //! use bevy::prelude::*;
 //! use bevy_text_edit::TextEdited;
@@ -84,11 +88,68 @@
 //!     }
 //! }
 //! ```
+//!
+//! `TextEdited::reason` tells how the edit ended: submitted with Enter, cancelled with Escape (the value is restored)
+//! or blurred by clicking elsewhere (see `TextEditConfig::blur_policy`).
+//!
+//! To react while the user is typing, listen to `TextChanged` instead. It is sent on every change of the value.
 
+pub mod commands;
+mod display;
 #[cfg(feature = "experimental")]
 pub mod experimental;
+pub mod filter;
+pub mod history;
+mod ime;
+mod layout;
+mod overlay;
+mod pointer;
+mod scroll;
+pub mod tab;
+pub mod validation;
 pub mod virtual_keyboard;
 
+use crate::display::{
+    hide_revealed_char,
+    TextDisplay,
+    TextMaskReveal,
+};
+use crate::filter::{
+    filter_insertion,
+    update_pattern_filter,
+    PatternFilter,
+    TextInputFilters,
+};
+use crate::history::TextEditHistory;
+use crate::ime::{
+    update_ime,
+    TextPreedit,
+};
+use crate::overlay::{
+    despawn_overlay,
+    spawn_overlay,
+    update_highlights,
+    update_overlay,
+    update_text_cursor,
+    TextCursor,
+};
+use crate::pointer::{
+    on_text_drag,
+    on_text_press,
+    TextClickCount,
+};
+use crate::scroll::{
+    scroll_area_on_mouse_wheel,
+    scroll_area_to_cursor,
+    scroll_to_cursor,
+    update_line_break,
+    TextScroll,
+};
+use crate::tab::TabOrder;
+use crate::validation::{
+    validate_text,
+    TextValidator,
+};
 use crate::virtual_keyboard::{
     VirtualKey,
     VirtualKeyboard,
@@ -111,13 +172,17 @@ use bevy::input::ButtonState;
 use bevy::log::error;
 use bevy::prelude::{
     in_state,
+    Added,
     Alpha,
     ButtonInput,
     Changed,
+    ChildOf,
+    Color,
     Commands,
     Component,
     Deref,
     DerefMut,
+    DetectChanges,
     Entity,
     EntityEvent,
     GlobalTransform,
@@ -128,7 +193,10 @@ use bevy::prelude::{
     MessageReader,
     MessageWriter,
     MouseButton,
+    Mut,
+    Or,
     Query,
+    Ref,
     Res,
     ResMut,
     Resource,
@@ -143,16 +211,31 @@ use bevy::prelude::{
 };
 use bevy::text::TextColor;
 use bevy::ui::Interaction;
-use regex_lite::Regex;
+use std::ops::Range;
+use unicode_segmentation::UnicodeSegmentation;
 
 macro_rules! plugin_systems {
     ( ) => {
         (
+            init_text_buffer,
+            (update_pattern_filter, update_line_break),
+            sync_text_buffer,
+            detect_text_change,
             listen_changing_focus,
             focus_text_box,
             listen_keyboard_input,
-            blink_cursor,
+            validate_text,
             display_placeholder,
+            display_disabled,
+            hide_revealed_char,
+            update_text,
+            (scroll_to_cursor, scroll_area_to_cursor, scroll_area_on_mouse_wheel).chain(),
+            update_overlay,
+            update_highlights,
+            blink_cursor,
+            update_text_cursor,
+            update_ime,
+            despawn_overlay,
         )
             .chain()
     };
@@ -175,10 +258,11 @@ where
     fn build(&self, app: &mut App) {
         app.add_plugins(VirtualKeyboardPlugin::new(self.states.clone()))
             .insert_resource(TextEditConfig::new())
-            .insert_resource(DisplayTextCursor(DEFAULT_CURSOR))
             .insert_resource(BlinkInterval(Timer::from_seconds(BLINK_INTERVAL, TimerMode::Repeating)))
             .add_message::<TextFocusChanged>()
-            .add_message::<TextEdited>();
+            .add_message::<TextEdited>()
+            .add_message::<TextChanged>()
+            .add_message::<TextInputRejected>();
 
         #[cfg(feature = "clipboard")]
         app.insert_resource(ClipboardMng::new());
@@ -218,18 +302,235 @@ impl TextEditPluginAnyState {
     }
 }
 
-const DEFAULT_CURSOR: char = '|';
 const BLINK_INTERVAL: f32 = 0.5;
 
-/// Current position of cursor in the text.
-#[derive(Component, Default)]
-pub struct CursorPosition {
-    pub pos: usize,
+/// The value of a `TextEditable`. This is the source of truth, the displayed `Text` is derived from it.
+///
+/// It is inserted automatically from the initial `Text` of the entity, unless the entity is spawned with one.
+#[derive(Component, Default, Clone, Debug)]
+pub struct TextEditBuffer {
+    /// The real text, without cursor.
+    pub value: String,
+
+    /// Byte position of cursor in `value`. It is always at a grapheme boundary, a position set inside a grapheme or
+    /// past the end is moved back to the previous boundary before it is used.
+    /// This is also the moving end of the selection.
+    pub cursor: usize,
+
+    /// Byte position where the selection starts. The selection spans from `anchor` to `cursor`.
+    /// It is kept at a grapheme boundary like `cursor`.
+    pub anchor: Option<usize>,
+
+    /// Grapheme column kept while moving between lines with Up/Down.
+    goal_column: Option<usize>,
 }
 
-/// The text that will be displayed as cursor. Default is `|`.
-#[derive(Resource, Deref, DerefMut)]
-pub struct DisplayTextCursor(char);
+impl TextEditBuffer {
+    pub fn new(value: impl Into<String>) -> Self {
+        let value = value.into();
+        Self {
+            cursor: value.len(),
+            value,
+            anchor: None,
+            goal_column: None,
+        }
+    }
+
+    /// Replace the value, clear the selection and move cursor to the end.
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        self.cursor = self.value.len();
+        self.anchor = None;
+        self.goal_column = None;
+    }
+
+    /// Byte range of the selected text. `None` if nothing is selected.
+    pub fn selection(&self) -> Option<Range<usize>> {
+        let anchor = self.floor_boundary(self.anchor?);
+        let cursor = self.floor_boundary(self.cursor);
+        (anchor != cursor).then(|| anchor.min(cursor)..anchor.max(cursor))
+    }
+
+    pub fn selected_text(&self) -> &str {
+        self.selection().map(|range| &self.value[range]).unwrap_or_default()
+    }
+
+    pub fn select_all(&mut self) {
+        self.anchor = Some(0);
+        self.cursor = self.value.len();
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.anchor = None;
+    }
+
+    /// Number of graphemes in the value.
+    pub fn len_graphemes(&self) -> usize {
+        self.value.graphemes(true).count()
+    }
+
+    /// Byte position of the start of the word before cursor.
+    fn prev_word_boundary(&self) -> usize {
+        self.value[..self.cursor]
+            .split_word_bound_indices()
+            .rev()
+            .find(|(_, segment)| is_word(segment))
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    }
+
+    /// Byte position of the end of the word after cursor.
+    fn next_word_boundary(&self) -> usize {
+        self.value[self.cursor..]
+            .split_word_bound_indices()
+            .find(|(_, segment)| is_word(segment))
+            .map(|(i, segment)| self.cursor + i + segment.len())
+            .unwrap_or(self.value.len())
+    }
+
+    /// Move cursor to `pos`. If `extend` is true, the selection is extended to `pos`, otherwise it is cleared.
+    fn move_cursor(&mut self, pos: usize, extend: bool) {
+        self.goal_column = None;
+        if !extend {
+            self.anchor = None;
+        } else if self.anchor.is_none() {
+            self.anchor = Some(self.cursor);
+        }
+        self.cursor = pos;
+    }
+
+    /// Select the byte range, with cursor at its end.
+    fn select_range(&mut self, range: Range<usize>) {
+        self.goal_column = None;
+        self.anchor = Some(range.start);
+        self.cursor = range.end;
+    }
+
+    /// Byte range of the word (or space, punctuation) at `pos`.
+    fn word_range(&self, pos: usize) -> Range<usize> {
+        self.value
+            .split_word_bound_indices()
+            .map(|(i, segment)| i..i + segment.len())
+            .find(|range| range.contains(&pos) || range.end == self.value.len())
+            .unwrap_or(pos..pos)
+    }
+
+    /// Byte position of the start of the line containing `pos`.
+    fn line_start(&self, pos: usize) -> usize {
+        self.value[..pos].rfind('\n').map(|i| i + 1).unwrap_or(0)
+    }
+
+    /// Byte position of the end of the line containing `pos`, before the line break.
+    fn line_end(&self, pos: usize) -> usize {
+        self.value[pos..]
+            .find('\n')
+            .map(|i| pos + i)
+            .unwrap_or(self.value.len())
+    }
+
+    /// Byte position of grapheme `column` in the line starting at `line_start`, or the line end if it is shorter.
+    fn column_position(&self, line_start: usize, column: usize) -> usize {
+        let line_end = self.line_end(line_start);
+        self.value[line_start..line_end]
+            .grapheme_indices(true)
+            .nth(column)
+            .map(|(i, _)| line_start + i)
+            .unwrap_or(line_end)
+    }
+
+    /// Move cursor to the previous line (or the next one if `down` is true), keeping its column.
+    fn move_vertically(&mut self, down: bool, extend: bool) {
+        let line_start = self.line_start(self.cursor);
+        let column = self
+            .goal_column
+            .unwrap_or_else(|| self.value[line_start..self.cursor].graphemes(true).count());
+        let pos = if down {
+            let line_end = self.line_end(self.cursor);
+            if line_end == self.value.len() {
+                line_end
+            } else {
+                self.column_position(line_end + 1, column)
+            }
+        } else if line_start == 0 {
+            0
+        } else {
+            self.column_position(self.line_start(line_start - 1), column)
+        };
+
+        self.move_cursor(pos, extend);
+        self.goal_column = Some(column);
+    }
+
+    /// Grapheme boundary at or before `pos`, within the value.
+    fn floor_boundary(&self, pos: usize) -> usize {
+        if pos >= self.value.len() {
+            return self.value.len();
+        }
+        self.value
+            .grapheme_indices(true)
+            .map(|(i, _)| i)
+            .take_while(|&i| i <= pos)
+            .last()
+            .unwrap_or(0)
+    }
+
+    /// Byte position of the grapheme boundary before cursor.
+    fn prev_boundary(&self) -> usize {
+        self.value[..self.cursor]
+            .grapheme_indices(true)
+            .next_back()
+            .map(|(i, _)| i)
+            .unwrap_or(0)
+    }
+
+    /// Byte position of the grapheme boundary after cursor.
+    fn next_boundary(&self) -> usize {
+        self.value[self.cursor..]
+            .graphemes(true)
+            .next()
+            .map(|g| self.cursor + g.len())
+            .unwrap_or(self.value.len())
+    }
+
+    /// Delete the selected text. Return false if nothing is selected.
+    fn delete_selection(&mut self) -> bool {
+        let Some(range) = self.selection() else {
+            self.anchor = None;
+            return false;
+        };
+
+        self.value.replace_range(range.clone(), "");
+        self.cursor = range.start;
+        self.anchor = None;
+        true
+    }
+
+    /// Insert text at cursor, replacing the selection.
+    fn insert(&mut self, text: &str) {
+        self.delete_selection();
+        self.value.insert_str(self.cursor, text);
+        self.cursor += text.len();
+    }
+
+    /// Delete the selection, or the grapheme (or word if `word` is true) before cursor.
+    fn delete_backward(&mut self, word: bool) {
+        if self.delete_selection() {
+            return;
+        }
+        let start = if word { self.prev_word_boundary() } else { self.prev_boundary() };
+        self.value.replace_range(start..self.cursor, "");
+        self.cursor = start;
+    }
+
+    /// Delete the selection, or the grapheme (or word if `word` is true) after cursor.
+    fn delete_forward(&mut self, word: bool) {
+        if self.delete_selection() {
+            return;
+        }
+        let end = if word { self.next_word_boundary() } else { self.next_boundary() };
+        self.value.replace_range(self.cursor..end, "");
+    }
+}
 
 /// Text cursor blink interval in millisecond.
 #[derive(Resource, Deref, DerefMut)]
@@ -264,7 +565,7 @@ pub struct TextEditFocus;
 /// }
 /// ```
 #[derive(Component)]
-#[require(Interaction, Text, GlobalTransform)]
+#[require(Interaction, Text, GlobalTransform, TextEditHistory)]
 pub struct TextEditable {
     /// Character in this list won't be added to the text.
     pub filter_out: Vec<String>,
@@ -272,9 +573,46 @@ pub struct TextEditable {
     /// If not empty, only character in this list will be added to the text.
     pub filter_in: Vec<String>,
 
-    /// Maximum text length. Default is 254. 0 means unlimited.
+    /// Maximum text length, measured in `length_unit`. Default is 254. 0 means unlimited.
+    /// Input beyond the limit is cut and `TextInputRejected` is sent.
     pub max_length: usize,
 
+    /// Unit of `max_length`. Default is graphemes, what users see as characters.
+    pub length_unit: LengthUnit,
+
+    /// Maximum number of lines of a multi-line text. 0 means unlimited.
+    pub max_lines: usize,
+
+    /// Multi-line text area. Enter inserts a new line, `Ctrl+Enter` or `TextEditConfig::multiline_commit_key` commits.
+    /// Single-line text doesn't wrap, it scrolls horizontally to keep the cursor visible. Set `Overflow::clip()` on
+    /// its parent node to hide the overflow.
+    /// Multi-line text scrolls vertically with its parent. Set `Overflow::scroll_y()` on the parent node, a scrollbar
+    /// can be attached to the parent's `ScrollPosition`.
+    pub multiline: bool,
+
+    /// Maximum height of a multi-line text box in lines, set on the parent node. 0 means it is not limited.
+    pub max_visible_lines: usize,
+
+    /// Selection highlight color. Default is `TextEditConfig::selection_color`.
+    pub selection_color: Option<Color>,
+
+    /// Selection highlight color when the text or the window is not focused.
+    /// Default is `TextEditConfig::inactive_selection_color`.
+    pub inactive_selection_color: Option<Color>,
+
+    /// The text can be focused, selected and copied, but not edited.
+    pub read_only: bool,
+
+    /// The text can't be focused. It is dimmed by `TextEditConfig::disabled_alpha`.
+    pub disabled: bool,
+
+    /// Display every grapheme as this character, for passwords. Copy and cut are disabled, the value is still in
+    /// `TextEditBuffer` and the edit events.
+    pub mask: Option<char>,
+
+    /// Display the last typed character of a masked text for `TextEditConfig::mask_reveal_duration`.
+    pub reveal_last_char: bool,
+
     /// Text placeholder. Display when text box is empty.
     pub placeholder: String,
     pub is_placeholder_shown: bool,
@@ -287,6 +625,16 @@ impl Default for TextEditable {
             filter_out: Default::default(),
             filter_in: Default::default(),
             max_length: 254,
+            length_unit: LengthUnit::default(),
+            max_lines: 0,
+            multiline: false,
+            max_visible_lines: 0,
+            selection_color: None,
+            inactive_selection_color: None,
+            read_only: false,
+            disabled: false,
+            mask: None,
+            reveal_last_char: false,
             placeholder: String::new(),
             is_placeholder_shown: false,
             orig_text_alpha: 1.0,
@@ -294,13 +642,128 @@ impl Default for TextEditable {
     }
 }
 
+/// Unit of `TextEditable::max_length`.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LengthUnit {
+    /// UTF-8 bytes, to fit a fixed size storage.
+    Bytes,
+    /// Unicode scalar values.
+    Chars,
+    /// Grapheme clusters. An emoji with a modifier or a letter with a combining accent counts as one.
+    #[default]
+    Graphemes,
+}
+
+impl LengthUnit {
+    /// Length of `text` in this unit.
+    pub fn measure(&self, text: &str) -> usize {
+        match self {
+            Self::Bytes => text.len(),
+            Self::Chars => text.chars().count(),
+            Self::Graphemes => text.graphemes(true).count(),
+        }
+    }
+}
+
+/// Sent when a text loses focus.
 #[derive(Message, EntityEvent, Clone)]
 pub struct TextEdited {
     pub text: String,
     pub entity: Entity,
+    pub reason: TextEditEndReason,
+}
+
+/// Why the edit of a text ended.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextEditEndReason {
+    /// Enter, or `Ctrl+Enter` in a text area.
+    Submit,
+    /// Escape. The value is restored to what it was when the text was focused.
+    Cancel,
+    /// Focus moved elsewhere. The value is kept or restored depending on `TextEditConfig::blur_policy`.
+    Blur,
+}
+
+/// What to do with the value when a text loses focus without submitting.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlurPolicy {
+    /// Keep the edited value.
+    #[default]
+    Commit,
+    /// Restore the value from when the text was focused.
+    Revert,
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextCursorStyle {
+    /// Vertical bar between glyphs.
+    #[default]
+    Bar,
+    /// Box covering the glyph after cursor.
+    Block,
+    /// Line under the glyph after cursor.
+    Underline,
+}
+
+/// What changed the value of a `TextEditable`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextChangeCause {
+    Typed,
+    Pasted,
+    Deleted,
+    Ime,
+    Undo,
+    Redo,
+    /// Restored to the value before focus when the edit is cancelled.
+    Reverted,
+    /// Changed by code, through `Text`, `TextEditBuffer` or `commands::TextEditCommandsExt`.
+    Programmatic,
+}
+
+/// Sent every time the value of a `TextEditable` changes, unlike `TextEdited` which is sent when the edit is finished.
+#[derive(Message, EntityEvent, Clone)]
+pub struct TextChanged {
+    pub entity: Entity,
+    pub old_text: String,
+    pub text: String,
+    pub cause: TextChangeCause,
+}
+
+/// A limit of `TextEditable`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextLimit {
+    MaxLength,
+    MaxLines,
+}
+
+/// Sent when input is cut because the text would exceed a limit.
+#[derive(Message, EntityEvent, Clone)]
+pub struct TextInputRejected {
+    pub entity: Entity,
+    /// The part of the input which is not inserted.
+    pub text: String,
+    pub limit: TextLimit,
+}
+
+/// Text alpha of a disabled `TextEditable` before it was dimmed.
+#[derive(Component)]
+struct TextDisabledStyle {
+    alpha: f32,
 }
 
-#[derive(Resource, Default)]
+/// Values of a `TextEditable` kept to detect changes made by code and to cancel an edit.
+/// A `TextEditable` without it is not initialized yet.
+#[derive(Component)]
+struct TextEditSnapshot {
+    /// The value when `TextChanged` was last sent.
+    last_value: String,
+    /// The value when the text was focused.
+    focus_value: String,
+    /// The `Text` last written by the plugin. Any other `Text` is a change made by code.
+    displayed_text: String,
+}
+
+#[derive(Resource)]
 pub struct TextEditConfig {
     pub enable_virtual_keyboard: bool,
 
@@ -310,8 +773,43 @@ pub struct TextEditConfig {
     /// Blink the text cursor.
     pub blink: bool,
 
+    /// Text cursor shape. Default is `TextCursorStyle::Bar`.
+    pub cursor_style: TextCursorStyle,
+
+    /// Thickness of the bar and underline cursor, in logical pixels. Default is 2.
+    pub cursor_width: f32,
+
+    /// Text cursor color. Default is the color of the text.
+    /// Block cursor is drawn over the glyph, so a translucent color should be used.
+    pub cursor_color: Option<Color>,
+
+    /// Selection highlight color.
+    /// The highlight is drawn behind the text node, so the background of a text box should be set on its parent node.
+    pub selection_color: Color,
+
+    /// Selection highlight color when the text or the window is not focused.
+    pub inactive_selection_color: Color,
+
+    /// Thickness of the underline of the text composed by IME, in logical pixels. Default is 1.
+    pub preedit_underline_width: f32,
+
+    /// Color of the underline of the text composed by IME. Default is the color of the text.
+    pub preedit_underline_color: Option<Color>,
+
+    /// Key which commits a multi-line text, in addition to `Ctrl+Enter`.
+    pub multiline_commit_key: Option<Key>,
+
+    /// What to do with the value when a text loses focus by clicking elsewhere. Default is `BlurPolicy::Commit`.
+    pub blur_policy: BlurPolicy,
+
     pub placeholder_alpha: f32,
 
+    /// Alpha multiplier of the text color of a disabled text. Default: 0.4.
+    pub disabled_alpha: f32,
+
+    /// Time (sec) the last typed character of a masked text stays displayed. Default: 1.
+    pub mask_reveal_duration: f32,
+
     /// Time (sec) wait before start repeat. Only apply to virtual keyboard.
     /// Default: 0.5.
     pub repeated_key_init_timeout: f32,
@@ -321,17 +819,36 @@ pub struct TextEditConfig {
     pub repeated_key_timeout: f32,
 }
 
-impl TextEditConfig {
-    pub fn new() -> Self {
+impl Default for TextEditConfig {
+    fn default() -> Self {
         Self {
+            enable_virtual_keyboard: false,
+            virtual_keyboard_pos: None,
+            blink: false,
+            cursor_style: TextCursorStyle::default(),
+            cursor_width: 2.,
+            cursor_color: None,
+            selection_color: Color::srgba(0.25, 0.45, 0.85, 0.6),
+            inactive_selection_color: Color::srgba(0.5, 0.5, 0.5, 0.4),
+            preedit_underline_width: 1.,
+            preedit_underline_color: None,
+            multiline_commit_key: None,
+            blur_policy: BlurPolicy::default(),
             placeholder_alpha: 0.2,
+            disabled_alpha: 0.4,
+            mask_reveal_duration: 1.,
             repeated_key_init_timeout: 0.5,
             repeated_key_timeout: 0.05,
-            ..Self::default()
         }
     }
 }
 
+impl TextEditConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
 #[cfg(feature = "clipboard")]
 #[derive(Resource)]
 struct ClipboardMng {
@@ -350,61 +867,337 @@ impl ClipboardMng {
             }
         }
     }
+
+    fn get_text(&mut self) -> Option<String> {
+        self.clipboard.as_mut()?.get_text().ok()
+    }
+
+    fn set_text(&mut self, text: &str) {
+        if let Some(clipboard) = self.clipboard.as_mut() {
+            if let Err(_e) = clipboard.set_text(text) {
+                #[cfg(feature = "log")]
+                error!("Failed to copy to clipboard: {}", _e);
+            }
+        }
+    }
 }
 
+type FocusedTextQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut TextEditBuffer,
+        &'static mut TextEditHistory,
+        &'static mut TextEditSnapshot,
+        &'static mut TextMaskReveal,
+        &'static mut TextPreedit,
+        Option<&'static TextValidator>,
+    ),
+    (With<TextEditFocus>, With<TextEditable>),
+>;
+
+/// End the edit of the focused texts matching `is_ended`. Return the number of texts which lost focus, a text with an
+/// invalid value stays focused on submit.
 fn unfocus_text_box(
     commands: &mut Commands,
-    text_focus: &mut Query<(Entity, &CursorPosition, &mut Text, &TextEditable), With<TextEditFocus>>,
-    ignore_entity: Option<Entity>,
+    text_focus: &mut FocusedTextQuery,
+    is_ended: impl Fn(Entity) -> bool,
+    reason: TextEditEndReason,
+    blur_policy: BlurPolicy,
     text_edited_event: &mut MessageWriter<TextEdited>,
+    text_changed_event: &mut MessageWriter<TextChanged>,
+) -> usize {
+    let mut unfocused = 0;
+    let revert =
+        reason == TextEditEndReason::Cancel || (reason == TextEditEndReason::Blur && blur_policy == BlurPolicy::Revert);
+    for (e, mut buffer, mut history, mut snapshot, mut reveal, mut preedit, validator) in text_focus.iter_mut() {
+        if !is_ended(e) {
+            continue;
+        }
+
+        let is_blocked = reason != TextEditEndReason::Cancel
+            && validator
+                .is_some_and(|validator| validator.block_commit && !validator.validate(&buffer.value).is_valid());
+        // Invalid value is not submitted, the text stays focused
+        if is_blocked && reason == TextEditEndReason::Submit {
+            continue;
+        }
+        commands.entity(e).remove::<TextEditFocus>();
+        unfocused += 1;
+        // Mask the revealed character and drop the composition before `Text` is derived again, the value may be
+        // reverted below
+        if reveal.range.is_some() {
+            reveal.range = None;
+        }
+        if preedit.is_composing() {
+            *preedit = TextPreedit::default();
+        }
+
+        if (revert || is_blocked) && buffer.value != snapshot.focus_value {
+            let before = buffer.clone();
+            buffer.set_value(snapshot.focus_value.clone());
+            record_change(
+                commands,
+                text_changed_event,
+                e,
+                before,
+                &buffer,
+                &mut history,
+                &mut snapshot,
+                &mut reveal,
+                TextChangeCause::Reverted,
+            );
+        }
+
+        let text_edited = TextEdited {
+            text: buffer.value.clone(),
+            entity: e,
+            reason,
+        };
+        text_edited_event.write(text_edited.clone());
+        commands.trigger(text_edited);
+    }
+    unfocused
+}
+
+/// Insert text from any input, typing, paste, IME or virtual keyboard, at the cursor.
+///
+/// The filters are applied, then line breaks are removed from single-line text and the result is cut to `max_length`
+/// and `max_lines`. Return the part of the text which is cut, with the limit it exceeds.
+fn insert_input(
+    buffer: &mut TextEditBuffer,
+    text: &str,
+    text_editable: &TextEditable,
+    pattern_filter: &PatternFilter,
+    filters: Option<&TextInputFilters>,
+) -> Option<(String, TextLimit)> {
+    // The value which is kept when the selection is replaced
+    let mut kept = buffer.clone();
+    kept.delete_selection();
+    let text = filter_insertion(&kept.value, kept.cursor, text, pattern_filter, filters)?;
+
+    // Filters can transform the insertion into line breaks too
+    let text: String = text
+        .chars()
+        .filter(|&c| c != '\r' && (text_editable.multiline || c != '\n'))
+        .collect();
+    if text.is_empty() {
+        return None;
+    }
+
+    let mut length = text_editable.length_unit.measure(&kept.value);
+    let mut lines = kept.value.matches('\n').count() + 1;
+    let mut exceeded = None;
+    for (i, grapheme) in text.grapheme_indices(true) {
+        let is_line_break = grapheme == "\n";
+        length += text_editable.length_unit.measure(grapheme);
+        if is_line_break && text_editable.max_lines > 0 && lines >= text_editable.max_lines {
+            exceeded = Some((i, TextLimit::MaxLines));
+        } else if text_editable.max_length > 0 && length > text_editable.max_length {
+            exceeded = Some((i, TextLimit::MaxLength));
+        }
+        if exceeded.is_some() {
+            break;
+        }
+        if is_line_break {
+            lines += 1;
+        }
+    }
+
+    let end = exceeded.map(|(i, _)| i).unwrap_or(text.len());
+    if end > 0 {
+        buffer.insert(&text[..end]);
+    }
+    exceeded.map(|(i, limit)| (text[i..].to_string(), limit))
+}
+
+fn send_input_rejected(commands: &mut Commands, entity: Entity, text: String, limit: TextLimit) {
+    let input_rejected = TextInputRejected { entity, text, limit };
+    commands.write_message(input_rejected.clone());
+    commands.trigger(input_rejected);
+}
+
+fn send_text_changed(
+    commands: &mut Commands,
+    text_changed_event: &mut MessageWriter<TextChanged>,
+    entity: Entity,
+    old_text: String,
+    text: String,
+    cause: TextChangeCause,
 ) {
-    for (e, cursor, mut text, text_editable) in text_focus.iter_mut() {
-        if ignore_entity.is_none() || e != ignore_entity.unwrap() {
-            commands.entity(e).remove::<TextEditFocus>();
+    let text_changed = TextChanged {
+        entity,
+        old_text,
+        text,
+        cause,
+    };
+    text_changed_event.write(text_changed.clone());
+    commands.trigger(text_changed);
+}
+
+/// Send `TextChanged` and save an undo step if the value is changed by an edit. Return false if it is not changed.
+/// The revealed character of a masked text is hidden by any change.
+fn record_change(
+    commands: &mut Commands,
+    text_changed_event: &mut MessageWriter<TextChanged>,
+    e: Entity,
+    before: TextEditBuffer,
+    buffer: &TextEditBuffer,
+    history: &mut TextEditHistory,
+    snapshot: &mut TextEditSnapshot,
+    reveal: &mut TextMaskReveal,
+    cause: TextChangeCause,
+) -> bool {
+    if buffer.value == before.value {
+        return false;
+    }
+
+    if reveal.range.is_some() {
+        reveal.range = None;
+    }
+    snapshot.last_value = buffer.value.clone();
+    send_text_changed(
+        commands,
+        text_changed_event,
+        e,
+        before.value.clone(),
+        buffer.value.clone(),
+        cause,
+    );
+    // Undo and redo move the states between history stacks by themselves
+    if !matches!(cause, TextChangeCause::Undo | TextChangeCause::Redo) {
+        history.push(before, cause);
+    }
+    true
+}
 
-            if text.len() > cursor.pos {
-                text.remove(cursor.pos);
+fn init_text_buffer(
+    mut commands: Commands,
+    query: Query<(Entity, &Text, &TextEditable, Option<&TextEditBuffer>, Option<&ChildOf>), Without<TextEditSnapshot>>,
+) {
+    for (e, text, text_editable, buffer, parent) in query.iter() {
+        // A buffer spawned with the text takes precedence over `Text`, which is updated from it
+        let buffer = buffer.cloned().unwrap_or_else(|| TextEditBuffer::new(text.0.clone()));
+        let nodes = spawn_overlay(&mut commands, e, parent);
+        commands
+            .entity(e)
+            .insert((
+                TextEditSnapshot {
+                    last_value: buffer.value.clone(),
+                    focus_value: buffer.value.clone(),
+                    displayed_text: text.0.clone(),
+                },
+                buffer,
+                TextClickCount::default(),
+                TextScroll::default(),
+                TextPreedit::default(),
+                TextMaskReveal::default(),
+                PatternFilter::new(&text_editable.filter_out, &text_editable.filter_in),
+                nodes,
+            ))
+            .observe(on_text_press)
+            .observe(on_text_drag);
+    }
+}
+
+/// Update the buffer when `Text` is changed from outside of the plugin, and keep its positions at grapheme
+/// boundaries when they are set from outside.
+fn sync_text_buffer(
+    mut query: Query<
+        (
+            Ref<Text>,
+            &mut TextEditBuffer,
+            &mut TextEditable,
+            &mut TextColor,
+            &mut TextEditSnapshot,
+        ),
+        Or<(Changed<Text>, Changed<TextEditBuffer>)>,
+    >,
+) {
+    for (text, mut buffer, mut text_editable, mut text_color, mut snapshot) in query.iter_mut() {
+        if text.is_changed() && text.0 != snapshot.displayed_text {
+            if text_editable.is_placeholder_shown {
+                text_editable.is_placeholder_shown = false;
+                text_color.set_alpha(text_editable.orig_text_alpha);
             }
-            commands.entity(e).remove::<CursorPosition>();
-            commands.entity(e).remove::<TextEditFocus>();
+            snapshot.displayed_text = text.0.clone();
+            buffer.set_value(text.0.clone());
+        }
 
-            let edited_text = if text_editable.is_placeholder_shown { String::new() } else { text.0.clone() };
+        clamp_positions(&mut buffer);
+    }
+}
 
-            let text_edited = TextEdited {
-                text: edited_text,
-                entity: e,
-            };
-            text_edited_event.write(text_edited.clone());
-            commands.trigger(text_edited);
+/// Move `cursor` and `anchor` set from outside back to grapheme boundaries within the value.
+/// The buffer is only marked changed when they move.
+fn clamp_positions(buffer: &mut Mut<TextEditBuffer>) {
+    let cursor = buffer.floor_boundary(buffer.cursor);
+    let anchor = buffer.anchor.map(|anchor| buffer.floor_boundary(anchor));
+    if buffer.cursor != cursor || buffer.anchor != anchor {
+        buffer.cursor = cursor;
+        buffer.anchor = anchor;
+    }
+}
+
+/// Send `TextChanged` for values changed outside of the plugin's input handling.
+fn detect_text_change(
+    mut commands: Commands,
+    mut query: Query<(Entity, &TextEditBuffer, &mut TextEditSnapshot), Changed<TextEditBuffer>>,
+    mut text_changed_event: MessageWriter<TextChanged>,
+) {
+    for (e, buffer, mut snapshot) in query.iter_mut() {
+        if buffer.value != snapshot.last_value {
+            let old_text = std::mem::replace(&mut snapshot.last_value, buffer.value.clone());
+            send_text_changed(
+                &mut commands,
+                &mut text_changed_event,
+                e,
+                old_text,
+                buffer.value.clone(),
+                TextChangeCause::Programmatic,
+            );
         }
     }
 }
 
 fn focus_text_box(
-    mut commands: Commands,
     mut focused_texts: Query<
-        (&mut Text, &mut TextColor, &mut TextEditable, Entity),
-        (With<TextEditFocus>, Without<CursorPosition>),
+        (
+            &mut Text,
+            &mut TextColor,
+            &mut TextEditable,
+            &mut TextEditBuffer,
+            &mut TextEditSnapshot,
+            &Interaction,
+        ),
+        Added<TextEditFocus>,
     >,
-    display_cursor: Res<DisplayTextCursor>,
 ) {
-    for (mut text, mut text_color, mut text_editable, e) in focused_texts.iter_mut() {
+    for (mut text, mut text_color, mut text_editable, mut buffer, mut snapshot, interaction) in focused_texts.iter_mut()
+    {
+        snapshot.focus_value = buffer.value.clone();
+
         if text_editable.is_placeholder_shown {
-            **text = String::new();
+            write_text(&mut text, &mut snapshot, String::new());
             text_editable.is_placeholder_shown = false;
             text_color.set_alpha(text_editable.orig_text_alpha);
         }
 
-        let pos = text.len();
-        commands.entity(e).insert(CursorPosition { pos });
-        text.push(**display_cursor);
+        // Text focused by a click gets the cursor at the clicked position
+        if *interaction != Interaction::Pressed {
+            buffer.cursor = buffer.value.len();
+        }
+        buffer.anchor = None;
     }
 }
 
+// Public to order systems after it, its parameters are internal
+#[allow(private_interfaces)]
 pub fn listen_changing_focus(
     mut commands: Commands,
     input: Res<ButtonInput<MouseButton>>,
-    mut text_interactions: Query<(&Interaction, Entity, &GlobalTransform), (Changed<Interaction>, With<TextEditable>)>,
+    mut text_interactions: Query<(&Interaction, Entity, &GlobalTransform, &TextEditable), Changed<Interaction>>,
     virtual_key_interaction: Query<&Interaction, (Changed<Interaction>, With<VirtualKey>, Without<TextEditable>)>,
     virtual_keyboard_interaction: Query<
         &Interaction,
@@ -415,47 +1208,119 @@ pub fn listen_changing_focus(
             Without<TextEditable>,
         ),
     >,
-    mut focusing_texts: Query<(Entity, &CursorPosition, &mut Text, &TextEditable), With<TextEditFocus>>,
+    mut focusing_texts: FocusedTextQuery,
+    focusing_editables: Query<&TextEditable, With<TextEditFocus>>,
     mut text_edited_event: MessageWriter<TextEdited>,
+    mut text_changed_event: MessageWriter<TextChanged>,
     mut focus_event: MessageWriter<TextFocusChanged>,
     mut events: MessageReader<KeyboardInput>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
     touches: Res<Touches>,
+    tab_order: TabOrder,
+    config: Res<TextEditConfig>,
 ) {
-    let mut unfocus_key_pressed = false;
+    let is_multiline = focusing_editables.iter().any(|text_editable| text_editable.multiline);
+    let is_composing = focusing_texts.iter().any(|(.., preedit, _)| preedit.is_composing());
+    let is_ctrl_pressed = keyboard_input.pressed(KeyCode::ControlRight) || keyboard_input.pressed(KeyCode::ControlLeft);
+    let is_shift_pressed = keyboard_input.pressed(KeyCode::ShiftRight) || keyboard_input.pressed(KeyCode::ShiftLeft);
+    let mut end_reason = None;
+    let mut tab_backward = None;
     for event in events.read() {
-        // Only trigger changes at the first time the key is pressed.
-        if event.state == ButtonState::Released {
+        // Only trigger changes at the first time the key is pressed. Keys belong to the IME while it composes.
+        if event.state == ButtonState::Released || is_composing {
             continue;
         }
         match &event.logical_key {
-            Key::Enter => unfocus_key_pressed = true,
-            Key::Escape => unfocus_key_pressed = true,
+            // Enter inserts a new line in multi-line text
+            Key::Enter if !is_multiline || is_ctrl_pressed => end_reason = Some(TextEditEndReason::Submit),
+            Key::Escape => end_reason = Some(TextEditEndReason::Cancel),
+            Key::Tab => tab_backward = Some(is_shift_pressed),
+            key if is_multiline && config.multiline_commit_key.as_ref() == Some(key) => {
+                end_reason = Some(TextEditEndReason::Submit)
+            }
             _ => {}
         }
     }
+    // A focused text which gets disabled loses focus
+    if end_reason.is_none() && focusing_editables.iter().any(|text_editable| text_editable.disabled) {
+        end_reason = Some(TextEditEndReason::Blur);
+    }
     let clicked_elsewhere = input.just_pressed(MouseButton::Left) || touches.any_just_pressed();
-    if unfocus_key_pressed
-        || (text_interactions.is_empty()
-            && virtual_key_interaction.is_empty()
-            && virtual_keyboard_interaction.is_empty()
-            && clicked_elsewhere)
+    // Disabled texts don't take focus, clicking them is clicking elsewhere
+    if end_reason.is_none()
+        && text_interactions
+            .iter()
+            .all(|(.., text_editable)| text_editable.disabled)
+        && virtual_key_interaction.is_empty()
+        && virtual_keyboard_interaction.is_empty()
+        && clicked_elsewhere
     {
-        unfocus_text_box(&mut commands, &mut focusing_texts, None, &mut text_edited_event);
-        focus_event.write(TextFocusChanged::Hide);
+        end_reason = Some(TextEditEndReason::Blur);
+    }
+    if let Some(reason) = end_reason {
+        let unfocused = unfocus_text_box(
+            &mut commands,
+            &mut focusing_texts,
+            |_| true,
+            reason,
+            config.blur_policy,
+            &mut text_edited_event,
+            &mut text_changed_event,
+        );
+        // The virtual keyboard stays to fix a value which can't be submitted
+        if unfocused > 0 {
+            focus_event.write(TextFocusChanged::Hide);
+        }
         return;
     }
 
-    for (interaction, e, global_transform) in text_interactions.iter_mut() {
-        if *interaction == Interaction::Pressed {
-            focus_event.write(TextFocusChanged::Show(global_transform.translation().y));
+    // Tab moves the focus to the next text, committing the current one like a click on the next text
+    let next_text = tab_backward
+        .zip(focusing_texts.iter().next())
+        .and_then(|(backward, (current, ..))| tab_order.next(current, backward));
+    if let Some(next_text) = next_text {
+        unfocus_text_box(
+            &mut commands,
+            &mut focusing_texts,
+            |_| true,
+            TextEditEndReason::Blur,
+            config.blur_policy,
+            &mut text_edited_event,
+            &mut text_changed_event,
+        );
+        commands.entity(next_text).insert(TextEditFocus);
+        if let Ok((text_editable, _, global_transform)) = tab_order.texts.get(next_text) {
+            focus_event.write(if text_editable.read_only {
+                TextFocusChanged::Hide
+            } else {
+                TextFocusChanged::Show(global_transform.translation().y)
+            });
+        }
+        return;
+    }
+
+    for (interaction, e, global_transform, text_editable) in text_interactions.iter_mut() {
+        if *interaction == Interaction::Pressed && !text_editable.disabled {
+            // No virtual keyboard to edit a read-only text
+            if !text_editable.read_only {
+                focus_event.write(TextFocusChanged::Show(global_transform.translation().y));
+            }
 
             let mut focusing_list = Vec::new();
-            for (focusing_e, _, _, _) in focusing_texts.iter() {
+            for (focusing_e, ..) in focusing_texts.iter() {
                 focusing_list.push(focusing_e);
             }
 
             // Unfocus all text box except which is currently clicked on
-            unfocus_text_box(&mut commands, &mut focusing_texts, Some(e), &mut text_edited_event);
+            unfocus_text_box(
+                &mut commands,
+                &mut focusing_texts,
+                |focusing_e| focusing_e != e,
+                TextEditEndReason::Blur,
+                config.blur_policy,
+                &mut text_edited_event,
+                &mut text_changed_event,
+            );
 
             if !focusing_list.contains(&e) {
                 commands.entity(e).insert(TextEditFocus);
@@ -465,122 +1330,222 @@ pub fn listen_changing_focus(
 }
 
 fn listen_keyboard_input(
+    mut commands: Commands,
     mut events: MessageReader<KeyboardInput>,
     mut ime_reader: MessageReader<Ime>,
-    mut edit_text: Query<(&mut Text, &mut CursorPosition, &TextEditable), With<TextEditFocus>>,
-    display_cursor: Res<DisplayTextCursor>,
+    mut edit_text: Query<
+        (
+            Entity,
+            &mut TextEditBuffer,
+            &mut TextEditHistory,
+            &mut TextEditSnapshot,
+            &TextEditable,
+            &PatternFilter,
+            Option<&TextInputFilters>,
+            &mut TextPreedit,
+            &mut TextMaskReveal,
+        ),
+        With<TextEditFocus>,
+    >,
+    mut text_changed_event: MessageWriter<TextChanged>,
     #[cfg(feature = "clipboard")] mut clipboard_mng: ResMut<ClipboardMng>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
 ) {
     let is_ctrl_pressed = keyboard_input.pressed(KeyCode::ControlRight) || keyboard_input.pressed(KeyCode::ControlLeft);
-    for (mut text, mut cursor, texteditable) in edit_text.iter_mut() {
-        let ignore_list = &texteditable.filter_out;
-        let allow_list = &texteditable.filter_in;
+    let is_shift_pressed = keyboard_input.pressed(KeyCode::ShiftRight) || keyboard_input.pressed(KeyCode::ShiftLeft);
+    for (e, mut buffer, mut history, mut snapshot, texteditable, pattern_filter, filters, mut preedit, mut reveal) in
+        edit_text.iter_mut()
+    {
         for ime in ime_reader.read() {
+            if texteditable.read_only {
+                continue;
+            }
             match ime {
-                Ime::Preedit { .. } => {}
+                Ime::Preedit { value, cursor, .. } => {
+                    if preedit.text != *value || preedit.cursor != *cursor {
+                        preedit.text = value.clone();
+                        preedit.cursor = *cursor;
+                    }
+                }
                 Ime::Commit { value, .. } => {
-                    let mut append_text = String::new();
-                    for char in value.chars() {
-                        if !is_ignored(ignore_list, allow_list, char.to_string())
-                            && (texteditable.max_length == 0
-                                || (text.len() + append_text.len()) < texteditable.max_length)
-                        {
-                            append_text.push(char);
-                        }
+                    if preedit.is_composing() {
+                        *preedit = TextPreedit::default();
                     }
-
-                    text.insert_str(cursor.pos, append_text.as_str());
-                    cursor.pos += append_text.len();
+                    let before = buffer.clone();
+                    if let Some((text, limit)) = insert_input(&mut buffer, value, texteditable, pattern_filter, filters)
+                    {
+                        send_input_rejected(&mut commands, e, text, limit);
+                    }
+                    record_change(
+                        &mut commands,
+                        &mut text_changed_event,
+                        e,
+                        before,
+                        &buffer,
+                        &mut history,
+                        &mut snapshot,
+                        &mut reveal,
+                        TextChangeCause::Ime,
+                    );
                 }
                 Ime::Enabled { .. } => {}
-                Ime::Disabled { .. } => {}
+                Ime::Disabled { .. } => {
+                    if preedit.is_composing() {
+                        *preedit = TextPreedit::default();
+                    }
+                }
             }
         }
 
         for event in events.read() {
-            if event.state == ButtonState::Released {
+            // Keys belong to the IME while it composes
+            if event.state == ButtonState::Released || preedit.is_composing() {
                 continue;
             }
 
+            // Up/Down keep the column of the first vertical move
+            if buffer.goal_column.is_some() && !matches!(event.logical_key, Key::ArrowUp | Key::ArrowDown) {
+                buffer.goal_column = None;
+            }
+
+            let before = buffer.clone();
+            let mut cause = TextChangeCause::Typed;
+            let mut rejected = None;
             match &event.logical_key {
-                Key::Space => {
-                    if is_ignored(ignore_list, allow_list, " ".into())
-                        || (texteditable.max_length > 0 && text.len() > texteditable.max_length)
-                    {
+                Key::Enter | Key::Space | Key::Backspace | Key::Delete if texteditable.read_only => continue,
+                Key::Enter => {
+                    if !texteditable.multiline || is_ctrl_pressed {
                         continue;
                     }
 
-                    text.insert(cursor.pos, ' ');
-                    cursor.pos += 1;
+                    rejected = insert_input(&mut buffer, "\n", texteditable, pattern_filter, filters);
+                }
+                Key::Space => {
+                    rejected = insert_input(&mut buffer, " ", texteditable, pattern_filter, filters);
                 }
                 Key::Backspace => {
-                    if cursor.pos > 0 {
-                        text.remove(cursor.pos - 1);
-                        cursor.pos -= 1;
-                    }
+                    buffer.delete_backward(is_ctrl_pressed);
+                    cause = TextChangeCause::Deleted;
                 }
                 Key::Delete => {
-                    if cursor.pos < text.len() - 1 {
-                        text.remove(cursor.pos + 1);
-                    }
+                    buffer.delete_forward(is_ctrl_pressed);
+                    cause = TextChangeCause::Deleted;
                 }
                 Key::Character(character) => {
-                    if character == "v" && is_ctrl_pressed && cfg!(feature = "clipboard") {
-                        #[cfg(feature = "clipboard")]
-                        if let Some(clipboard) = clipboard_mng.clipboard.as_mut() {
-                            let append_text: String = clipboard
-                                .get_text()
-                                .unwrap_or_default()
-                                .chars()
-                                .filter(|&c| !is_ignored(ignore_list, allow_list, c.to_string()))
-                                .collect();
-
-                            text.insert_str(cursor.pos, append_text.as_str());
-                            cursor.pos += append_text.len();
-                        } else {
-                            continue;
+                    let shortcut = if is_ctrl_pressed { character.to_lowercase() } else { String::new() };
+                    match shortcut.as_str() {
+                        "a" => buffer.select_all(),
+                        "z" | "y" | "x" | "v" if texteditable.read_only => continue,
+                        "z" if is_shift_pressed => {
+                            history.redo(&mut buffer);
+                            cause = TextChangeCause::Redo;
                         }
-                    } else {
-                        if is_ignored(ignore_list, allow_list, character.to_string())
-                            || (texteditable.max_length > 0 && text.len() > texteditable.max_length)
-                        {
-                            continue;
+                        "z" => {
+                            history.undo(&mut buffer);
+                            cause = TextChangeCause::Undo;
+                        }
+                        "y" => {
+                            history.redo(&mut buffer);
+                            cause = TextChangeCause::Redo;
+                        }
+                        // The value of a masked text can't be copied
+                        #[cfg(feature = "clipboard")]
+                        "c" | "x" if texteditable.mask.is_some() => continue,
+                        #[cfg(feature = "clipboard")]
+                        "c" | "x" => {
+                            // Copy the whole text if nothing is selected
+                            let copied_text = if buffer.selection().is_some() {
+                                buffer.selected_text()
+                            } else {
+                                buffer.value.as_str()
+                            };
+                            clipboard_mng.set_text(copied_text);
+
+                            if shortcut == "x" {
+                                if buffer.selection().is_none() {
+                                    buffer.select_all();
+                                }
+                                buffer.delete_selection();
+                                cause = TextChangeCause::Deleted;
+                            }
                         }
-                        let append_text = character.to_string();
+                        // Ctrl+Shift+v is the same, the clipboard is always read as plain text.
+                        #[cfg(feature = "clipboard")]
+                        "v" => {
+                            let Some(clipboard_text) = clipboard_mng.get_text() else {
+                                continue;
+                            };
 
-                        text.insert_str(cursor.pos, append_text.as_str());
-                        cursor.pos += append_text.len();
+                            rejected =
+                                insert_input(&mut buffer, &clipboard_text, texteditable, pattern_filter, filters);
+                            cause = TextChangeCause::Pasted;
+                        }
+                        // Other shortcuts, and the clipboard ones without the clipboard feature, don't type
+                        _ if is_ctrl_pressed => continue,
+                        _ if texteditable.read_only => continue,
+                        _ => {
+                            rejected = insert_input(&mut buffer, character, texteditable, pattern_filter, filters);
+                        }
                     }
                 }
                 Key::ArrowLeft => {
-                    if cursor.pos > 0 {
-                        text.remove(cursor.pos);
-
-                        cursor.pos -= 1;
-                        text.insert(cursor.pos, **display_cursor);
-                    }
+                    let pos = match buffer.selection() {
+                        Some(range) if !is_shift_pressed => range.start,
+                        _ if is_ctrl_pressed => buffer.prev_word_boundary(),
+                        _ => buffer.prev_boundary(),
+                    };
+                    buffer.move_cursor(pos, is_shift_pressed);
                 }
                 Key::ArrowRight => {
-                    if cursor.pos < text.len() - 1 {
-                        text.remove(cursor.pos);
-
-                        cursor.pos += 1;
-                        text.insert(cursor.pos, **display_cursor);
-                    }
+                    let pos = match buffer.selection() {
+                        Some(range) if !is_shift_pressed => range.end,
+                        _ if is_ctrl_pressed => buffer.next_word_boundary(),
+                        _ => buffer.next_boundary(),
+                    };
+                    buffer.move_cursor(pos, is_shift_pressed);
+                }
+                Key::ArrowUp | Key::ArrowDown if texteditable.multiline => {
+                    buffer.move_vertically(event.logical_key == Key::ArrowDown, is_shift_pressed);
                 }
                 Key::Home => {
-                    text.remove(cursor.pos);
-                    cursor.pos = 0;
-                    text.insert(0, **display_cursor);
+                    let pos = if is_ctrl_pressed { 0 } else { buffer.line_start(buffer.cursor) };
+                    buffer.move_cursor(pos, is_shift_pressed);
                 }
                 Key::End => {
-                    text.remove(cursor.pos);
-                    cursor.pos = text.len();
-                    text.push(**display_cursor);
+                    let pos = if is_ctrl_pressed { buffer.value.len() } else { buffer.line_end(buffer.cursor) };
+                    buffer.move_cursor(pos, is_shift_pressed);
                 }
                 _ => continue,
             }
+
+            if let Some((text, limit)) = rejected {
+                send_input_rejected(&mut commands, e, text, limit);
+            }
+            let is_changed = record_change(
+                &mut commands,
+                &mut text_changed_event,
+                e,
+                before,
+                &buffer,
+                &mut history,
+                &mut snapshot,
+                &mut reveal,
+                cause,
+            );
+            if !is_changed {
+                history.break_merge();
+                continue;
+            }
+
+            if texteditable.mask.is_some() && texteditable.reveal_last_char && cause == TextChangeCause::Typed {
+                let start = buffer.value[..buffer.cursor]
+                    .grapheme_indices(true)
+                    .next_back()
+                    .map(|(i, _)| i)
+                    .unwrap_or(0);
+                reveal.range = Some(start..buffer.cursor);
+                reveal.stopwatch.reset();
+            }
         }
     }
 }
@@ -588,60 +1553,113 @@ fn listen_keyboard_input(
 fn blink_cursor(
     time: Res<Time>,
     mut blink_interval: ResMut<BlinkInterval>,
-    display_text_cursor: Res<DisplayTextCursor>,
-    mut query: Query<(&mut Text, &CursorPosition), (With<TextEditFocus>, With<TextEditable>)>,
+    mut query: Query<&mut TextCursor>,
     config: Res<TextEditConfig>,
 ) {
     blink_interval.tick(time.delta());
-    for (mut text, cursor_pos) in query.iter_mut() {
-        if config.blink && blink_interval.just_finished() && text.len() > cursor_pos.pos {
-            let current_cursor = text.as_bytes()[cursor_pos.pos] as char;
-            let next_cursor = if current_cursor != **display_text_cursor { **display_text_cursor } else { ' ' };
-            text.replace_range(cursor_pos.pos..(cursor_pos.pos + 1), String::from(next_cursor).as_str());
+    for mut cursor in query.iter_mut() {
+        if !config.blink {
+            if !cursor.visible {
+                cursor.visible = true;
+            }
+        } else if blink_interval.just_finished() {
+            cursor.visible = !cursor.visible;
+        }
+    }
+}
+
+/// Dim the text color of disabled texts, and restore it when they are enabled again.
+fn display_disabled(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut TextEditable, &mut TextColor, Option<&TextDisabledStyle>), Changed<TextEditable>>,
+    config: Res<TextEditConfig>,
+) {
+    for (e, mut text_editable, mut text_color, style) in query.iter_mut() {
+        // While the placeholder is shown, the text alpha is kept in `orig_text_alpha`
+        let alpha = if text_editable.is_placeholder_shown { text_editable.orig_text_alpha } else { text_color.alpha() };
+        let new_alpha = match (text_editable.disabled, style) {
+            (true, None) => {
+                commands.entity(e).insert(TextDisabledStyle { alpha });
+                alpha * config.disabled_alpha
+            }
+            (false, Some(style)) => {
+                commands.entity(e).remove::<TextDisabledStyle>();
+                style.alpha
+            }
+            _ => continue,
+        };
+
+        if text_editable.is_placeholder_shown {
+            text_editable.orig_text_alpha = new_alpha;
+        } else {
+            text_color.set_alpha(new_alpha);
         }
     }
 }
 
 fn display_placeholder(
-    mut query: Query<(&mut Text, &mut TextColor, &mut TextEditable), Without<TextEditFocus>>,
+    mut query: Query<
+        (
+            &mut Text,
+            &mut TextColor,
+            &mut TextEditable,
+            &TextEditBuffer,
+            &mut TextEditSnapshot,
+        ),
+        Without<TextEditFocus>,
+    >,
     config: Res<TextEditConfig>,
 ) {
-    for (mut text, mut text_color, mut text_editable) in query.iter_mut() {
-        if text.is_empty() && !text_editable.is_placeholder_shown && !text_editable.placeholder.is_empty() {
-            **text = text_editable.placeholder.clone();
+    for (mut text, mut text_color, mut text_editable, buffer, mut snapshot) in query.iter_mut() {
+        if buffer.value.is_empty() && !text_editable.is_placeholder_shown && !text_editable.placeholder.is_empty() {
+            write_text(&mut text, &mut snapshot, text_editable.placeholder.clone());
             text_editable.is_placeholder_shown = true;
             text_editable.orig_text_alpha = text_color.alpha();
             text_color.set_alpha(config.placeholder_alpha);
+        } else if !buffer.value.is_empty() && text_editable.is_placeholder_shown {
+            text_editable.is_placeholder_shown = false;
+            text_color.set_alpha(text_editable.orig_text_alpha);
         }
     }
 }
 
-fn is_ignored(ignore_list: &Vec<String>, allow_list: &Vec<String>, key: String) -> bool {
-    for pattern in ignore_list {
-        if let Ok(re) = Regex::new(pattern) {
-            if re.is_match(&key) {
-                return true;
-            }
-        } else if *pattern == key {
-            return true;
+/// Derive the displayed `Text` from `TextEditBuffer`.
+fn update_text(
+    mut query: Query<
+        (
+            &mut Text,
+            &mut TextEditSnapshot,
+            &TextEditBuffer,
+            &TextEditable,
+            &TextPreedit,
+            &TextMaskReveal,
+        ),
+        Or<(
+            Changed<TextEditBuffer>,
+            Changed<TextEditable>,
+            Changed<TextPreedit>,
+            Changed<TextMaskReveal>,
+        )>,
+    >,
+) {
+    for (mut text, mut snapshot, buffer, text_editable, preedit, reveal) in query.iter_mut() {
+        if text_editable.is_placeholder_shown {
+            continue;
         }
+        let displayed_text = TextDisplay::new(buffer, preedit, text_editable, reveal).text;
+        write_text(&mut text, &mut snapshot, displayed_text);
     }
+}
 
-    if !allow_list.is_empty() {
-        let mut is_included = false;
-        for pattern in allow_list {
-            if let Ok(re) = Regex::new(pattern) {
-                if re.is_match(&key) {
-                    is_included = true;
-                    break;
-                }
-            } else if *pattern == key {
-                is_included = true;
-                break;
-            }
-        }
-        return !is_included;
+/// Write `Text` from the plugin, so that `sync_text_buffer` doesn't take it for a change made by code.
+fn write_text(text: &mut Mut<Text>, snapshot: &mut TextEditSnapshot, value: String) {
+    if text.0 != value {
+        text.0 = value.clone();
     }
+    snapshot.displayed_text = value;
+}
 
-    false
+/// A word segment contains at least one letter or number, other segments are spaces and punctuations.
+fn is_word(segment: &str) -> bool {
+    segment.chars().any(char::is_alphanumeric)
 }
@@ -0,0 +1,464 @@
+//! Nodes drawn over a `TextEditable`, such as the text cursor and the IME preedit underline, or under it, such as the
+//! selection highlight.
+//!
+//! Text nodes can't have child nodes without breaking their measurement, so overlays are spawned as absolute
+//! positioned siblings of the text node and follow its position every frame.
+
+use crate::display::{
+    TextDisplay,
+    TextMaskReveal,
+};
+use crate::ime::TextPreedit;
+use crate::layout::{
+    cursor_rect,
+    selection_rects,
+    text_rows,
+};
+use crate::{
+    TextCursorStyle,
+    TextEditBuffer,
+    TextEditConfig,
+    TextEditFocus,
+    TextEditable,
+};
+use bevy::math::{
+    Rect,
+    Vec2,
+};
+use bevy::picking::Pickable;
+use bevy::prelude::{
+    BackgroundColor,
+    ChildOf,
+    Children,
+    Color,
+    Commands,
+    Component,
+    DetectChanges,
+    Entity,
+    Has,
+    Mut,
+    Node,
+    Query,
+    Ref,
+    RemovedComponents,
+    Res,
+    TextColor,
+    Val,
+    Visibility,
+    Window,
+    With,
+    Without,
+    ZIndex,
+};
+use bevy::text::{
+    ComputedTextBlock,
+    TextLayoutInfo,
+};
+use bevy::ui::{
+    ComputedNode,
+    FocusPolicy,
+    PositionType,
+    UiGlobalTransform,
+};
+use bevy::window::PrimaryWindow;
+
+/// Overlay nodes of a `TextEditable`.
+#[derive(Component)]
+pub(crate) struct TextEditNodes {
+    pub cursor: Entity,
+    pub highlight: Entity,
+    pub preedit: Entity,
+}
+
+/// Overlay container, covering its owner text node.
+#[derive(Component)]
+pub(crate) struct TextEditOverlay {
+    owner: Entity,
+}
+
+/// The text cursor node.
+#[derive(Component)]
+pub(crate) struct TextCursor {
+    /// Blink state. The cursor is only displayed when its text is focused.
+    pub visible: bool,
+}
+
+/// One rect of the selection highlight or of the preedit underline.
+#[derive(Component)]
+pub(crate) struct TextOverlayRect;
+
+pub(crate) fn spawn_overlay(commands: &mut Commands, owner: Entity, parent: Option<&ChildOf>) -> TextEditNodes {
+    let cursor = commands
+        .spawn((
+            TextCursor { visible: true },
+            BackgroundColor::default(),
+            Node {
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            Visibility::Hidden,
+            FocusPolicy::Pass,
+            Pickable::IGNORE,
+        ))
+        .id();
+
+    let preedit = commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            FocusPolicy::Pass,
+            Pickable::IGNORE,
+        ))
+        .id();
+
+    let overlay = commands
+        .spawn((
+            TextEditOverlay { owner },
+            Node {
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            FocusPolicy::Pass,
+            Pickable::IGNORE,
+        ))
+        .add_children(&[preedit, cursor])
+        .id();
+
+    // Drawn before its siblings, so behind the text.
+    let highlight = commands
+        .spawn((
+            TextEditOverlay { owner },
+            Node {
+                position_type: PositionType::Absolute,
+                ..Default::default()
+            },
+            ZIndex(-1),
+            FocusPolicy::Pass,
+            Pickable::IGNORE,
+        ))
+        .id();
+
+    if let Some(parent) = parent {
+        commands.entity(parent.parent()).add_children(&[highlight, overlay]);
+    }
+
+    TextEditNodes {
+        cursor,
+        highlight,
+        preedit,
+    }
+}
+
+/// Keep overlays on top of their text node.
+pub(crate) fn update_overlay(
+    texts: Query<(&ComputedNode, &UiGlobalTransform, Option<&ChildOf>), With<TextEditable>>,
+    parents: Query<(&ComputedNode, &UiGlobalTransform)>,
+    mut overlays: Query<(&TextEditOverlay, &mut Node)>,
+) {
+    for (overlay, mut node) in overlays.iter_mut() {
+        let Ok((computed_node, transform, parent)) = texts.get(overlay.owner) else {
+            continue;
+        };
+
+        // Absolute nodes are placed relatively to the padding box of their parent, or to the viewport for root nodes.
+        let origin = parent
+            .and_then(|parent| parents.get(parent.parent()).ok())
+            .map(|(parent_node, parent_transform)| {
+                parent_transform.translation - 0.5 * parent_node.size() + parent_node.border().min_inset
+                    - parent_node.scroll_position
+            })
+            .unwrap_or(Vec2::ZERO);
+
+        let scale = computed_node.inverse_scale_factor();
+        let pos = (transform.translation - 0.5 * computed_node.size() - origin) * scale;
+        set_position(&mut node, pos);
+        set_size(&mut node, computed_node.size() * scale);
+    }
+}
+
+pub(crate) fn update_text_cursor(
+    texts: Query<(
+        Ref<TextEditBuffer>,
+        Ref<TextPreedit>,
+        Ref<TextEditable>,
+        Ref<TextMaskReveal>,
+        &TextEditNodes,
+        &ComputedTextBlock,
+        Ref<TextLayoutInfo>,
+        Ref<ComputedNode>,
+        &TextColor,
+        Option<Ref<TextEditFocus>>,
+    )>,
+    mut cursors: Query<(&mut TextCursor, &mut Node, &mut Visibility, &mut BackgroundColor), Without<TextEditable>>,
+    config: Res<TextEditConfig>,
+) {
+    for (buffer, preedit, text_editable, reveal, nodes, block, layout, computed_node, text_color, focus) in texts.iter()
+    {
+        let Ok((mut cursor, mut node, mut visibility, mut background)) = cursors.get_mut(nodes.cursor) else {
+            continue;
+        };
+
+        // Keep the cursor visible while editing
+        if buffer.is_changed() && !cursor.visible {
+            cursor.visible = true;
+        }
+
+        // The IME can hide the cursor while composing
+        let has_cursor = !preedit.is_composing() || preedit.cursor.is_some();
+        let new_visibility = if focus.is_some() && cursor.visible && has_cursor {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        if *visibility != new_visibility {
+            *visibility = new_visibility;
+        }
+        let Some(focus) = focus else {
+            continue;
+        };
+
+        let color = config.cursor_color.unwrap_or(text_color.0);
+        if background.0 != color {
+            background.0 = color;
+        }
+
+        // The position only moves with the text and its layout
+        let is_moved = focus.is_added()
+            || buffer.is_changed()
+            || preedit.is_changed()
+            || text_editable.is_changed()
+            || reveal.is_changed()
+            || layout.is_changed()
+            || computed_node.is_changed()
+            || config.is_changed();
+        if !is_moved {
+            continue;
+        }
+        let display = TextDisplay::new(&buffer, &preedit, &text_editable, &reveal);
+        let Some(display_cursor) = display.cursor else {
+            continue;
+        };
+
+        let rows = text_rows(block, &display.text);
+        let Some(rect) = cursor_rect(&rows, display_cursor) else {
+            continue;
+        };
+
+        let scale = computed_node.inverse_scale_factor();
+        let pos = (rect.min + computed_node.content_inset().min_inset) * scale;
+        let height = rect.height() * scale;
+        // Block and underline cover the glyph after cursor, or half of the line height at the end of a row.
+        let glyph_width = if rect.width() > 0. { rect.width() * scale } else { height / 2. };
+        let (pos, size) = match config.cursor_style {
+            TextCursorStyle::Bar => (pos, Vec2::new(config.cursor_width, height)),
+            TextCursorStyle::Block => (pos, Vec2::new(glyph_width, height)),
+            TextCursorStyle::Underline => (
+                Vec2::new(pos.x, pos.y + height - config.cursor_width),
+                Vec2::new(glyph_width, config.cursor_width),
+            ),
+        };
+        set_position(&mut node, pos);
+        set_size(&mut node, size);
+    }
+}
+
+/// Update the selection highlight and the underline of the text composed by IME.
+pub(crate) fn update_highlights(
+    mut commands: Commands,
+    texts: Query<(
+        Ref<TextEditBuffer>,
+        Ref<TextPreedit>,
+        Ref<TextEditable>,
+        Ref<TextMaskReveal>,
+        &TextEditNodes,
+        &ComputedTextBlock,
+        Ref<TextLayoutInfo>,
+        Ref<ComputedNode>,
+        &TextColor,
+        Has<TextEditFocus>,
+    )>,
+    children: Query<&Children>,
+    mut rects: Query<(&mut Node, &mut BackgroundColor), With<TextOverlayRect>>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    config: Res<TextEditConfig>,
+) {
+    let is_window_focused = window.single().map(|window| window.focused).unwrap_or(true);
+    for (buffer, preedit, text_editable, reveal, nodes, block, layout, computed_node, text_color, is_focused) in
+        texts.iter()
+    {
+        let selection_color = if is_focused && is_window_focused {
+            text_editable.selection_color.unwrap_or(config.selection_color)
+        } else {
+            text_editable
+                .inactive_selection_color
+                .unwrap_or(config.inactive_selection_color)
+        };
+        let underline_color = config.preedit_underline_color.unwrap_or(text_color.0);
+
+        // Nothing to draw, and nothing drawn to remove
+        let has_rects = |container: Entity| children.get(container).is_ok_and(|c| !c.is_empty());
+        if buffer.selection().is_none()
+            && !preedit.is_composing()
+            && !has_rects(nodes.highlight)
+            && !has_rects(nodes.preedit)
+        {
+            continue;
+        }
+
+        // The rects only move with the text and its layout, the colors follow the focus
+        let is_moved = buffer.is_changed()
+            || preedit.is_changed()
+            || text_editable.is_changed()
+            || reveal.is_changed()
+            || layout.is_changed()
+            || computed_node.is_changed()
+            || config.is_changed();
+        if !is_moved {
+            set_rects_color(&children, &mut rects, nodes.highlight, selection_color);
+            set_rects_color(&children, &mut rects, nodes.preedit, underline_color);
+            continue;
+        }
+
+        let display = TextDisplay::new(&buffer, &preedit, &text_editable, &reveal);
+        let rows = text_rows(block, &display.text);
+        let scale = computed_node.inverse_scale_factor();
+        let to_node = |rect: Rect| {
+            let min = (rect.min + computed_node.content_inset().min_inset) * scale;
+            Rect::from_corners(min, min + rect.size() * scale)
+        };
+
+        let selected_rects: Vec<Rect> = display
+            .selection
+            .map(|range| selection_rects(&rows, range).into_iter().map(to_node).collect())
+            .unwrap_or_default();
+        update_rects(
+            &mut commands,
+            &children,
+            &mut rects,
+            nodes.highlight,
+            &selected_rects,
+            selection_color,
+        );
+
+        let underline_rects: Vec<Rect> = display
+            .preedit
+            .map(|range| {
+                selection_rects(&rows, range)
+                    .into_iter()
+                    .map(|rect| {
+                        let rect = to_node(rect);
+                        Rect::new(
+                            rect.min.x,
+                            rect.max.y - config.preedit_underline_width,
+                            rect.max.x,
+                            rect.max.y,
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        update_rects(
+            &mut commands,
+            &children,
+            &mut rects,
+            nodes.preedit,
+            &underline_rects,
+            underline_color,
+        );
+    }
+}
+
+/// Display `node_rects` as children of `container`. Spawned rects are reused, the difference is spawned or despawned.
+fn update_rects(
+    commands: &mut Commands,
+    children: &Query<&Children>,
+    rects: &mut Query<(&mut Node, &mut BackgroundColor), With<TextOverlayRect>>,
+    container: Entity,
+    node_rects: &[Rect],
+    color: Color,
+) {
+    let spawned = children.get(container).map(|c| c.to_vec()).unwrap_or_default();
+    for (i, rect) in node_rects.iter().enumerate() {
+        if let Some((mut node, mut background)) = spawned.get(i).and_then(|&e| rects.get_mut(e).ok()) {
+            set_position(&mut node, rect.min);
+            set_size(&mut node, rect.size());
+            if background.0 != color {
+                background.0 = color;
+            }
+        } else {
+            let rect_node = commands
+                .spawn((
+                    TextOverlayRect,
+                    Node {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(rect.min.x),
+                        top: Val::Px(rect.min.y),
+                        width: Val::Px(rect.width()),
+                        height: Val::Px(rect.height()),
+                        ..Default::default()
+                    },
+                    BackgroundColor(color),
+                    FocusPolicy::Pass,
+                    Pickable::IGNORE,
+                ))
+                .id();
+            commands.entity(container).add_child(rect_node);
+        }
+    }
+    for &e in spawned.iter().skip(node_rects.len()) {
+        commands.entity(e).despawn();
+    }
+}
+
+fn set_rects_color(
+    children: &Query<&Children>,
+    rects: &mut Query<(&mut Node, &mut BackgroundColor), With<TextOverlayRect>>,
+    container: Entity,
+    color: Color,
+) {
+    let Ok(spawned) = children.get(container) else {
+        return;
+    };
+    for &e in spawned {
+        if let Ok((_, mut background)) = rects.get_mut(e) {
+            if background.0 != color {
+                background.0 = color;
+            }
+        }
+    }
+}
+
+/// Despawn overlays of removed `TextEditable`.
+pub(crate) fn despawn_overlay(
+    mut commands: Commands,
+    mut removed: RemovedComponents<TextEditable>,
+    overlays: Query<(Entity, &TextEditOverlay)>,
+) {
+    for owner in removed.read() {
+        for (e, overlay) in overlays.iter() {
+            if overlay.owner == owner {
+                commands.entity(e).despawn();
+            }
+        }
+    }
+}
+
+fn set_position(node: &mut Mut<Node>, pos: Vec2) {
+    if node.left != Val::Px(pos.x) {
+        node.left = Val::Px(pos.x);
+    }
+    if node.top != Val::Px(pos.y) {
+        node.top = Val::Px(pos.y);
+    }
+}
+
+fn set_size(node: &mut Mut<Node>, size: Vec2) {
+    if node.width != Val::Px(size.x) {
+        node.width = Val::Px(size.x);
+    }
+    if node.height != Val::Px(size.y) {
+        node.height = Val::Px(size.y);
+    }
+}
@@ -0,0 +1,196 @@
+//! Mouse and touch interaction with a `TextEditable`.
+
+use crate::display::{
+    TextDisplay,
+    TextMaskReveal,
+};
+use crate::history::TextEditHistory;
+use crate::ime::TextPreedit;
+use crate::layout::{
+    hit_position,
+    text_rows,
+};
+use crate::{
+    clamp_positions,
+    TextEditBuffer,
+    TextEditFocus,
+    TextEditable,
+};
+use bevy::picking::pointer::{
+    Location,
+    PointerButton,
+};
+use bevy::prelude::{
+    ButtonInput,
+    Component,
+    Drag,
+    Has,
+    KeyCode,
+    On,
+    Pointer,
+    Press,
+    Query,
+    Res,
+    Time,
+    Window,
+    With,
+};
+use bevy::text::ComputedTextBlock;
+use bevy::ui::{
+    ComputedNode,
+    UiGlobalTransform,
+};
+use bevy::window::PrimaryWindow;
+
+/// Max time (sec) between clicks to count as a double or triple click.
+const MULTI_CLICK_INTERVAL: f32 = 0.4;
+
+/// Consecutive clicks on a text. A double click selects a word, a triple click selects the line.
+#[derive(Component, Default)]
+pub(crate) struct TextClickCount {
+    count: u8,
+    last_click: f32,
+}
+
+/// Place the cursor at the pressed glyph, or select by word or line on multiple clicks.
+pub(crate) fn on_text_press(
+    press: On<Pointer<Press>>,
+    mut texts: Query<(
+        &mut TextEditBuffer,
+        &mut TextEditHistory,
+        &mut TextClickCount,
+        &TextEditable,
+        &TextPreedit,
+        &TextMaskReveal,
+        &ComputedTextBlock,
+        &ComputedNode,
+        &UiGlobalTransform,
+        Has<TextEditFocus>,
+    )>,
+    window: Query<&Window, With<PrimaryWindow>>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    time: Res<Time>,
+) {
+    if press.button != PointerButton::Primary {
+        return;
+    }
+    let Ok((
+        mut buffer,
+        mut history,
+        mut click,
+        text_editable,
+        preedit,
+        reveal,
+        block,
+        computed_node,
+        transform,
+        is_focused,
+    )) = texts.get_mut(press.entity)
+    else {
+        return;
+    };
+    let Ok(window) = window.single() else {
+        return;
+    };
+    // Nothing to place, placeholder might be displayed
+    if buffer.value.is_empty() || text_editable.disabled {
+        return;
+    }
+    // Positions set from outside are only repaired by the plugin systems, after picking
+    clamp_positions(&mut buffer);
+
+    let now = time.elapsed_secs();
+    click.count = if now - click.last_click < MULTI_CLICK_INTERVAL { click.count % 3 + 1 } else { 1 };
+    click.last_click = now;
+
+    let pos = text_position(
+        &press.pointer_location,
+        window,
+        &TextDisplay::new(&buffer, preedit, text_editable, reveal),
+        block,
+        computed_node,
+        transform,
+    );
+    // Typing after moving the cursor is a new undo step
+    history.break_merge();
+    match click.count {
+        // Words of a masked text are hidden
+        2 if text_editable.mask.is_some() => buffer.select_all(),
+        2 => {
+            let range = buffer.word_range(pos);
+            buffer.select_range(range);
+        }
+        3 => {
+            let range = buffer.line_start(pos)..buffer.line_end(pos);
+            buffer.select_range(range);
+        }
+        _ => {
+            let is_shift_pressed =
+                keyboard_input.pressed(KeyCode::ShiftRight) || keyboard_input.pressed(KeyCode::ShiftLeft);
+            buffer.move_cursor(pos, is_focused && is_shift_pressed);
+        }
+    }
+}
+
+/// Extend the selection to the dragged glyph.
+pub(crate) fn on_text_drag(
+    drag: On<Pointer<Drag>>,
+    mut texts: Query<(
+        &mut TextEditBuffer,
+        &mut TextEditHistory,
+        &TextEditable,
+        &TextPreedit,
+        &TextMaskReveal,
+        &ComputedTextBlock,
+        &ComputedNode,
+        &UiGlobalTransform,
+    )>,
+    window: Query<&Window, With<PrimaryWindow>>,
+) {
+    if drag.button != PointerButton::Primary {
+        return;
+    }
+    let Ok((mut buffer, mut history, text_editable, preedit, reveal, block, computed_node, transform)) =
+        texts.get_mut(drag.entity)
+    else {
+        return;
+    };
+    let Ok(window) = window.single() else {
+        return;
+    };
+    if buffer.value.is_empty() || text_editable.disabled {
+        return;
+    }
+    clamp_positions(&mut buffer);
+
+    let pos = text_position(
+        &drag.pointer_location,
+        window,
+        &TextDisplay::new(&buffer, preedit, text_editable, reveal),
+        block,
+        computed_node,
+        transform,
+    );
+    if pos != buffer.cursor {
+        buffer.move_cursor(pos, true);
+        history.break_merge();
+    }
+}
+
+/// Byte position in the buffer at the pointer location.
+fn text_position(
+    location: &Location,
+    window: &Window,
+    display: &TextDisplay,
+    block: &ComputedTextBlock,
+    computed_node: &ComputedNode,
+    transform: &UiGlobalTransform,
+) -> usize {
+    // Pointer location is in logical pixels, relative to the window. Layout is in physical pixels, relative to the text.
+    let pos = location.position * window.scale_factor();
+    let point = transform.inverse().transform_point2(pos) + 0.5 * computed_node.size()
+        - computed_node.content_inset().min_inset;
+
+    let rows = text_rows(block, &display.text);
+    display.to_value(hit_position(&rows, point))
+}
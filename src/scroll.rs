@@ -0,0 +1,267 @@
+//! Keep the text cursor in view when the text overflows its node.
+//!
+//! Single-line text doesn't wrap and is moved horizontally with its `UiTransform`. Its parent clips the overflow.
+//! The node is widened by the scroll offset, so that it still covers the visible part of the text and gets its clicks.
+//! Multi-line text is scrolled vertically by the `ScrollPosition` of its parent.
+
+use crate::display::{
+    TextDisplay,
+    TextMaskReveal,
+};
+use crate::ime::TextPreedit;
+use crate::layout::{
+    cursor_rect,
+    text_rows,
+};
+use crate::{
+    TextEditBuffer,
+    TextEditConfig,
+    TextEditFocus,
+    TextEditable,
+};
+use bevy::input::mouse::{
+    MouseScrollUnit,
+    MouseWheel,
+};
+use bevy::prelude::{
+    Changed,
+    ChildOf,
+    Component,
+    DetectChanges,
+    Has,
+    MessageReader,
+    Node,
+    Query,
+    Ref,
+    Res,
+    Val,
+    Without,
+};
+use bevy::text::{
+    ComputedTextBlock,
+    LineBreak,
+    TextLayout,
+    TextLayoutInfo,
+};
+use bevy::ui::{
+    ComputedNode,
+    Interaction,
+    ScrollPosition,
+    UiGlobalTransform,
+    UiTransform,
+    Val2,
+};
+
+/// Line height used before the text is laid out.
+const DEFAULT_LINE_HEIGHT: f32 = 20.;
+
+/// Horizontal scroll offset of a single-line text, in physical pixels.
+#[derive(Component, Default)]
+pub(crate) struct TextScroll {
+    x: f32,
+    /// `Node::min_width` set by the user, replaced while the node is widened.
+    min_width: Option<Val>,
+    /// `UiTransform::translation` set by the user, offset while the text is scrolled.
+    translation: Option<Val2>,
+}
+
+/// Single-line text doesn't wrap, also when `TextEditable::multiline` is changed.
+pub(crate) fn update_line_break(mut texts: Query<(&TextEditable, &mut TextLayout), Changed<TextEditable>>) {
+    for (text_editable, mut text_layout) in texts.iter_mut() {
+        let linebreak = match text_layout.linebreak {
+            _ if !text_editable.multiline => LineBreak::NoWrap,
+            LineBreak::NoWrap => LineBreak::default(),
+            linebreak => linebreak,
+        };
+        if text_layout.linebreak != linebreak {
+            text_layout.linebreak = linebreak;
+        }
+    }
+}
+
+/// Scroll single-line text horizontally so the cursor stays visible. Unfocused text snaps back to its start.
+pub(crate) fn scroll_to_cursor(
+    mut texts: Query<(
+        &TextEditBuffer,
+        &TextPreedit,
+        &TextEditable,
+        &TextMaskReveal,
+        &ComputedTextBlock,
+        &TextLayoutInfo,
+        &ComputedNode,
+        &mut TextScroll,
+        &mut UiTransform,
+        &mut Node,
+        Has<TextEditFocus>,
+    )>,
+    config: Res<TextEditConfig>,
+) {
+    for (
+        buffer,
+        preedit,
+        text_editable,
+        reveal,
+        block,
+        layout,
+        computed_node,
+        mut scroll,
+        mut transform,
+        mut node,
+        is_focused,
+    ) in texts.iter_mut()
+    {
+        let scale = computed_node.inverse_scale_factor();
+        // Width of the node before it was widened by the scroll offset
+        let node_width = computed_node.size().x - scroll.x;
+        let mut x = 0.;
+        if is_focused && !text_editable.multiline && !text_editable.is_placeholder_shown {
+            let inset = computed_node.content_inset();
+            let width = node_width - inset.min_inset.x - inset.max_inset.x;
+            let cursor_width = config.cursor_width / scale;
+            let display = TextDisplay::new(buffer, preedit, text_editable, reveal);
+            let rows = text_rows(block, &display.text);
+            if let Some(rect) = cursor_rect(&rows, display.view_position()) {
+                x = scroll.x;
+                if rect.min.x < x {
+                    x = rect.min.x;
+                } else if rect.min.x + cursor_width > x + width {
+                    x = rect.min.x + cursor_width - width;
+                }
+                // Don't leave empty space at the end when the text is shortened
+                x = x.clamp(0., (layout.size.x + cursor_width - width).max(0.));
+            }
+        }
+
+        if scroll.x != x {
+            scroll.x = x;
+
+            // Moved to the left, the node must be wider to still cover the visible part
+            if x > 0. {
+                if scroll.min_width.is_none() {
+                    scroll.min_width = Some(node.min_width);
+                }
+                node.min_width = Val::Px((node_width + x) * scale);
+
+                // The offset is in pixels, a horizontal translation in other units is replaced while scrolled
+                let translation = *scroll.translation.get_or_insert(transform.translation);
+                let translation_x = match translation.x {
+                    Val::Px(px) => px,
+                    _ => 0.,
+                };
+                transform.translation = Val2 {
+                    x: Val::Px(translation_x - x * scale),
+                    y: translation.y,
+                };
+            } else {
+                if let Some(min_width) = scroll.min_width.take() {
+                    node.min_width = min_width;
+                }
+                if let Some(translation) = scroll.translation.take() {
+                    transform.translation = translation;
+                }
+            }
+        }
+    }
+}
+
+/// Scroll the parent of a focused text area so the cursor stays visible, and limit its height to
+/// `TextEditable::max_visible_lines`.
+pub(crate) fn scroll_area_to_cursor(
+    texts: Query<(
+        Ref<TextEditBuffer>,
+        &TextPreedit,
+        &TextEditable,
+        &TextMaskReveal,
+        &ComputedTextBlock,
+        Ref<TextLayoutInfo>,
+        &ComputedNode,
+        &UiGlobalTransform,
+        &ChildOf,
+        Has<TextEditFocus>,
+    )>,
+    mut parents: Query<(&ComputedNode, &UiGlobalTransform, &mut ScrollPosition, &mut Node), Without<TextEditable>>,
+) {
+    for (buffer, preedit, text_editable, reveal, block, layout, computed_node, transform, parent, is_focused) in
+        texts.iter()
+    {
+        if !text_editable.multiline {
+            continue;
+        }
+        let Ok((parent_node, parent_transform, mut scroll, mut node)) = parents.get_mut(parent.parent()) else {
+            continue;
+        };
+        let scale = parent_node.inverse_scale_factor();
+        let parent_inset = parent_node.content_inset();
+
+        if text_editable.max_visible_lines > 0 {
+            let height = text_editable.max_visible_lines as f32 * line_height(&layout)
+                + parent_inset.min_inset.y
+                + parent_inset.max_inset.y;
+            let max_height = Val::Px(height * scale);
+            if node.max_height != max_height {
+                node.max_height = max_height;
+            }
+        }
+
+        // Layout is updated one frame after the buffer, follow both so a new line is scrolled into view.
+        if !is_focused || !(buffer.is_changed() || layout.is_changed()) {
+            continue;
+        }
+        let display = TextDisplay::new(&buffer, preedit, text_editable, reveal);
+        let rows = text_rows(block, &display.text);
+        let Some(rect) = cursor_rect(&rows, display.view_position()) else {
+            continue;
+        };
+
+        let text_top =
+            transform.translation.y - 0.5 * computed_node.size().y + computed_node.content_inset().min_inset.y;
+        let cursor_top = text_top + rect.min.y;
+        let cursor_bottom = text_top + rect.max.y;
+        let view_top = parent_transform.translation.y - 0.5 * parent_node.size().y + parent_inset.min_inset.y;
+        let view_bottom = parent_transform.translation.y + 0.5 * parent_node.size().y - parent_inset.max_inset.y;
+
+        let delta = if cursor_top < view_top {
+            cursor_top - view_top
+        } else if cursor_bottom > view_bottom {
+            cursor_bottom - view_bottom
+        } else {
+            0.
+        };
+        if delta != 0. {
+            scroll.y += delta * scale;
+        }
+    }
+}
+
+/// Scroll the parent of a hovered text area with the mouse wheel.
+pub(crate) fn scroll_area_on_mouse_wheel(
+    mut wheel_events: MessageReader<MouseWheel>,
+    texts: Query<(&TextEditable, &Interaction, &TextLayoutInfo, &ComputedNode, &ChildOf)>,
+    mut parents: Query<&mut ScrollPosition, Without<TextEditable>>,
+) {
+    for event in wheel_events.read() {
+        for (text_editable, interaction, layout, computed_node, parent) in texts.iter() {
+            if !text_editable.multiline || *interaction == Interaction::None {
+                continue;
+            }
+            let Ok(mut scroll) = parents.get_mut(parent.parent()) else {
+                continue;
+            };
+
+            let delta = match event.unit {
+                MouseScrollUnit::Line => event.y * line_height(layout) * computed_node.inverse_scale_factor(),
+                MouseScrollUnit::Pixel => event.y,
+            };
+            scroll.y -= delta;
+        }
+    }
+}
+
+/// Height of one line of text, in physical pixels.
+fn line_height(layout: &TextLayoutInfo) -> f32 {
+    layout
+        .run_geometry
+        .first()
+        .map(|run| run.bounds.height())
+        .unwrap_or(DEFAULT_LINE_HEIGHT)
+}
@@ -0,0 +1,105 @@
+//! Move focus between texts with Tab and Shift+Tab.
+//!
+//! Texts are visited in ascending `TextTabIndex`, then texts without index in UI tree order. Focus wraps around
+//! within the nearest `TextTabGroup` ancestor of the focused text, or within the whole UI without group.
+//! ```rust
+//! use bevy::prelude::*;
+//! use bevy_text_edit::tab::{
+//!     TextTabGroup,
+//!     TextTabIndex,
+//! };
+//! use bevy_text_edit::TextEditable;
+//!
+//! fn setup(mut commands: Commands) {
+//!     commands.spawn((Node::default(), TextTabGroup)).with_children(|parent| {
+//!         parent.spawn((TextEditable::default(), Text::new("Second"), TextTabIndex(2)));
+//!         parent.spawn((TextEditable::default(), Text::new("First"), TextTabIndex(1)));
+//!         parent.spawn((TextEditable::default(), Text::new("Third")));
+//!     });
+//! }
+//! ```
+
+use crate::TextEditable;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::{
+    ChildOf,
+    Children,
+    Component,
+    Entity,
+    GlobalTransform,
+    Node,
+    Query,
+    With,
+    Without,
+};
+
+/// Position of a `TextEditable` in Tab order. A text with negative index is skipped.
+#[derive(Component, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TextTabIndex(pub i32);
+
+/// Tab order wraps around within the texts under this node.
+#[derive(Component, Default)]
+pub struct TextTabGroup;
+
+#[derive(SystemParam)]
+pub(crate) struct TabOrder<'w, 's> {
+    pub texts: Query<
+        'w,
+        's,
+        (
+            &'static TextEditable,
+            Option<&'static TextTabIndex>,
+            &'static GlobalTransform,
+        ),
+    >,
+    children: Query<'w, 's, &'static Children>,
+    parents: Query<'w, 's, &'static ChildOf>,
+    groups: Query<'w, 's, (), With<TextTabGroup>>,
+    roots: Query<'w, 's, Entity, (With<Node>, Without<ChildOf>)>,
+}
+
+impl TabOrder<'_, '_> {
+    /// The text to focus after `current`, or before it if `backward` is true.
+    pub fn next(&self, current: Entity, backward: bool) -> Option<Entity> {
+        let mut scope = Vec::new();
+        match self.parents.iter_ancestors(current).find(|&e| self.groups.contains(e)) {
+            Some(group) => self.collect_texts(group, &mut scope),
+            None => {
+                let mut roots: Vec<Entity> = self.roots.iter().collect();
+                roots.sort();
+                for root in roots {
+                    self.collect_texts(root, &mut scope);
+                }
+            }
+        }
+
+        let mut order: Vec<(Entity, Option<i32>)> = scope
+            .into_iter()
+            .filter_map(|e| {
+                let (text_editable, tab_index, _) = self.texts.get(e).ok()?;
+                let index = tab_index.map(|tab_index| tab_index.0);
+                let is_tabbable = !text_editable.disabled && index.is_none_or(|index| index >= 0);
+                (is_tabbable || e == current).then_some((e, index))
+            })
+            .collect();
+        // Stable sort keeps the tree order of texts with the same index
+        order.sort_by_key(|(_, index)| (index.is_none(), index.unwrap_or_default()));
+
+        let len = order.len();
+        let pos = order.iter().position(|(e, _)| *e == current)?;
+        let next = if backward { (pos + len - 1) % len } else { (pos + 1) % len };
+        (next != pos).then_some(order[next].0)
+    }
+
+    /// Texts under `root`, in tree order.
+    fn collect_texts(&self, root: Entity, texts: &mut Vec<Entity>) {
+        if self.texts.contains(root) {
+            texts.push(root);
+        }
+        if let Ok(children) = self.children.get(root) {
+            for &child in children {
+                self.collect_texts(child, texts);
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Validation of the whole value of a `TextEditable`.
+
+use crate::TextEditBuffer;
+#[cfg(feature = "log")]
+use bevy::log::error;
+use bevy::prelude::{
+    Changed,
+    Component,
+    Or,
+    Query,
+};
+use regex_lite::Regex;
+
+/// Validate the value of a `TextEditable` on every change. The result is stored in the component `TextValidation`.
+/// ```rust
+/// use bevy::prelude::*;
+/// use bevy_text_edit::validation::TextValidator;
+/// use bevy_text_edit::TextEditable;
+///
+/// fn setup(mut commands: Commands) {
+///     commands.spawn((
+///         TextEditable::default(),
+///         TextValidator::new()
+///             .pattern(r"[0-9]+(\.[0-9]*)?", "Not a decimal number")
+///             .rule(|value| match value.parse::<f32>() {
+///                 Ok(number) if number > 100. => Err("Too large".into()),
+///                 _ => Ok(()),
+///             })
+///             .block_commit(true),
+///         Text::new("1.5"),
+///     ));
+/// }
+/// ```
+#[derive(Component, Default)]
+#[require(TextValidation)]
+pub struct TextValidator {
+    rules: Vec<Rule>,
+
+    /// Don't commit an invalid value: Enter keeps the text focused, clicking elsewhere restores the previous value.
+    pub block_commit: bool,
+}
+
+enum Rule {
+    Pattern(Regex, String),
+    Custom(Box<dyn Fn(&str) -> Result<(), String> + Send + Sync>),
+}
+
+impl TextValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The whole value must match the regex `pattern`, otherwise it is invalid with `message`.
+    /// Invalid pattern is ignored.
+    pub fn pattern(mut self, pattern: &str, message: impl Into<String>) -> Self {
+        match Regex::new(&format!("^(?:{})$", pattern)) {
+            Ok(re) => self.rules.push(Rule::Pattern(re, message.into())),
+            Err(_e) => {
+                #[cfg(feature = "log")]
+                error!("Invalid validation pattern \"{}\": {}", pattern, _e);
+            }
+        }
+        self
+    }
+
+    /// Custom rule, returning the error message of an invalid value.
+    pub fn rule(mut self, rule: impl Fn(&str) -> Result<(), String> + Send + Sync + 'static) -> Self {
+        self.rules.push(Rule::Custom(Box::new(rule)));
+        self
+    }
+
+    pub fn block_commit(mut self, block_commit: bool) -> Self {
+        self.block_commit = block_commit;
+        self
+    }
+
+    /// Check `value` against all rules. The first failed rule gives the error message.
+    pub fn validate(&self, value: &str) -> TextValidation {
+        for rule in &self.rules {
+            let result = match rule {
+                Rule::Pattern(re, message) => {
+                    if re.is_match(value) {
+                        Ok(())
+                    } else {
+                        Err(message.clone())
+                    }
+                }
+                Rule::Custom(rule) => rule(value),
+            };
+            if let Err(message) = result {
+                return TextValidation::Invalid(message);
+            }
+        }
+
+        TextValidation::Valid
+    }
+}
+
+/// Validation result of a `TextEditable` with `TextValidator`.
+#[derive(Component, Default, Clone, Debug, PartialEq, Eq)]
+pub enum TextValidation {
+    #[default]
+    Valid,
+    Invalid(String),
+}
+
+impl TextValidation {
+    pub fn is_valid(&self) -> bool {
+        *self == Self::Valid
+    }
+}
+
+pub(crate) fn validate_text(
+    mut query: Query<
+        (&TextEditBuffer, &TextValidator, &mut TextValidation),
+        Or<(Changed<TextEditBuffer>, Changed<TextValidator>)>,
+    >,
+) {
+    for (buffer, validator, mut validation) in query.iter_mut() {
+        let result = validator.validate(&buffer.value);
+        if *validation != result {
+            *validation = result;
+        }
+    }
+}
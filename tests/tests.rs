@@ -1,18 +1,54 @@
+use bevy::camera::NormalizedRenderTarget;
+use bevy::image::TextureAtlasPlugin;
 use bevy::input::keyboard::{
     Key,
     KeyboardInput,
 };
+use bevy::input::mouse::MouseButtonInput;
 use bevy::input::{
     ButtonState,
     InputPlugin,
 };
+use bevy::picking::backend::HitData;
+use bevy::picking::pointer::{
+    Location,
+    PointerButton,
+    PointerId,
+};
 use bevy::prelude::*;
+use bevy::text::TextPlugin;
 use bevy::time::TimePlugin;
+use bevy::ui::UiPlugin;
+use bevy::window::{
+    Ime,
+    WindowRef,
+};
+use bevy_text_edit::commands::TextEditCommandsExt;
+use bevy_text_edit::filter::{
+    FilterResult,
+    TextInputFilters,
+};
+use bevy_text_edit::history::TextEditHistory;
+use bevy_text_edit::tab::TextTabIndex;
+use bevy_text_edit::validation::{
+    TextValidation,
+    TextValidator,
+};
 use bevy_text_edit::{
+    BlurPolicy,
+    LengthUnit,
+    TextChangeCause,
+    TextChanged,
+    TextEditBuffer,
+    TextEditConfig,
+    TextEditEndReason,
     TextEditFocus,
     TextEditPluginAnyState,
     TextEditable,
     TextEdited,
+    TextFocusChanged,
+    TextInputRejected,
+    TextLimit,
 };
 
 const TEXT_1: &str = "Text_Section1";
@@ -25,14 +61,24 @@ fn arrow() {
     // 1 Arrow left
     send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
     app.update();
-    let text1 = app.world().get::<Text>(text1_e).unwrap();
-    assert_eq!(text1.0, "Text_Section|1".to_string());
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section|1");
 
     // 1 Arrow right
     send_key(app.world_mut(), KeyCode::ArrowRight, Key::ArrowRight);
     app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1|");
+}
+
+#[test]
+fn text_without_cursor() {
+    let (mut app, text1_e, _) = setup(vec![], vec![], 0);
+
+    send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    send_key(app.world_mut(), KeyCode::KeyA, Key::Character("a".into()));
+    app.update();
     let text1 = app.world().get::<Text>(text1_e).unwrap();
-    assert_eq!(text1.0, "Text_Section1|".to_string());
+    assert_eq!(text1.0, "Text_Sectiona1".to_string());
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Sectiona|1");
 }
 
 #[test]
@@ -42,8 +88,7 @@ fn backspace() {
     // 1 Backspace
     send_key(app.world_mut(), KeyCode::Backspace, Key::Backspace);
     app.update();
-    let text1 = app.world().get::<Text>(text1_e).unwrap();
-    assert_eq!(text1.0, "Text_Section|".to_string());
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section|");
 }
 
 #[test]
@@ -54,8 +99,7 @@ fn input_text() {
     send_key(app.world_mut(), KeyCode::KeyA, Key::Character("a".into()));
 
     app.update();
-    let text1 = app.world().get::<Text>(text1_e).unwrap();
-    assert_eq!(text1.0, "Text_Section1 a|".to_string());
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1 a|");
 }
 
 #[test]
@@ -66,14 +110,12 @@ fn delete() {
     send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
     send_key(app.world_mut(), KeyCode::Delete, Key::Delete);
     app.update();
-    let text1 = app.world().get::<Text>(text1_e).unwrap();
-    assert_eq!(text1.0, "Text_Section|".to_string());
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section|");
 
     // Delete at the end of line
     send_key(app.world_mut(), KeyCode::Delete, Key::Delete);
     app.update();
-    let text1 = app.world().get::<Text>(text1_e).unwrap();
-    assert_eq!(text1.0, "Text_Section|".to_string());
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section|");
 }
 
 #[test]
@@ -83,20 +125,17 @@ fn home_end() {
     // Home
     send_key(app.world_mut(), KeyCode::Home, Key::Home);
     app.update();
-    let text1 = app.world().get::<Text>(text1_e).unwrap();
-    assert_eq!(text1.0, "|Text_Section1".to_string());
+    assert_eq!(text_with_cursor(app.world(), text1_e), "|Text_Section1");
 
     // Backspace at the beginning
     send_key(app.world_mut(), KeyCode::Backspace, Key::Backspace);
     app.update();
-    let text1 = app.world().get::<Text>(text1_e).unwrap();
-    assert_eq!(text1.0, "|Text_Section1".to_string());
+    assert_eq!(text_with_cursor(app.world(), text1_e), "|Text_Section1");
 
     // End
     send_key(app.world_mut(), KeyCode::End, Key::End);
     app.update();
-    let text1 = app.world().get::<Text>(text1_e).unwrap();
-    assert_eq!(text1.0, "Text_Section1|".to_string());
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1|");
 }
 
 #[test]
@@ -110,8 +149,7 @@ fn ignore_char_test() {
     send_key(app.world_mut(), KeyCode::Digit4, Key::Character("4".into()));
 
     app.update();
-    let text1 = app.world().get::<Text>(text1_e).unwrap();
-    assert_eq!(text1.0, "Text_Section1b4|".to_string());
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1b4|");
 }
 
 #[test]
@@ -127,8 +165,57 @@ fn allow_char_test() {
     send_key(app.world_mut(), KeyCode::Digit4, Key::Character("4".into()));
 
     app.update();
-    let text1 = app.world().get::<Text>(text1_e).unwrap();
-    assert_eq!(text1.0, "Text_Section1 a13|".to_string());
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1 a13|");
+}
+
+#[test]
+fn filter_changed() {
+    let (mut app, text1_e, _) = setup(vec![], vec!["[0-9]".into()], 0);
+
+    send_key(app.world_mut(), KeyCode::KeyA, Key::Character("a".into()));
+    send_key(app.world_mut(), KeyCode::Digit1, Key::Character("1".into()));
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section11|");
+
+    app.world_mut().get_mut::<TextEditable>(text1_e).unwrap().filter_in = vec!["[a-z]".into()];
+    send_key(app.world_mut(), KeyCode::KeyA, Key::Character("a".into()));
+    send_key(app.world_mut(), KeyCode::Digit1, Key::Character("1".into()));
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section11a|");
+}
+
+#[test]
+fn custom_filter() {
+    let (mut app, text1_e, _) = setup(vec![], vec![], 0);
+
+    // No repeated spaces, and letters are upper case
+    app.world_mut().entity_mut(text1_e).insert(
+        TextInputFilters::new()
+            .with(|value: &str, cursor: usize, insertion: &str| {
+                if insertion == " " && value[..cursor].ends_with(' ') {
+                    FilterResult::Reject
+                } else {
+                    FilterResult::Accept
+                }
+            })
+            .with(|_: &str, _: usize, insertion: &str| FilterResult::Transform(insertion.to_uppercase())),
+    );
+
+    send_key(app.world_mut(), KeyCode::Space, Key::Space);
+    send_key(app.world_mut(), KeyCode::Space, Key::Space);
+    send_key(app.world_mut(), KeyCode::KeyA, Key::Character("a".into()));
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1 A|");
+
+    // Line breaks from a filter are removed from single-line text
+    app.world_mut().entity_mut(text1_e).insert(
+        TextInputFilters::new()
+            .with(|_: &str, _: usize, insertion: &str| FilterResult::Transform(insertion.replace(';', "\n"))),
+    );
+    send_key(app.world_mut(), KeyCode::Semicolon, Key::Character(";".into()));
+    send_key(app.world_mut(), KeyCode::KeyB, Key::Character("b".into()));
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1 Ab|");
 }
 
 #[test]
@@ -142,71 +229,1098 @@ fn max_length() {
     send_key(app.world_mut(), KeyCode::KeyA, Key::Character("a".into()));
 
     app.update();
-    let text1 = app.world().get::<Text>(text1_e).unwrap();
-    assert_eq!(text1.0, "Text_Section1aa|".to_string());
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1aa|");
 }
 
-fn setup(ignore: Vec<String>, allow: Vec<String>, max_length: usize) -> (App, Entity, Entity) {
-    let mut app = App::new();
-    let mut text1 = Entity::PLACEHOLDER;
-    let mut text2 = Entity::PLACEHOLDER;
+#[test]
+fn max_length_ime() {
+    let (mut app, text1_e, _) = setup(vec!["b".into()], vec![], 15);
 
-    app.add_plugins((
-        WindowPlugin::default(),
-        InputPlugin,
-        TimePlugin,
-        TextEditPluginAnyState::any(),
-    ));
+    // Filters are applied before the text is cut to the limit
+    send_ime_commit(app.world_mut(), "abcde");
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1ac|");
 
-    app.world_mut()
-        .spawn(Node {
-            width: Val::Percent(100.),
-            height: Val::Percent(100.),
-            flex_direction: FlexDirection::Column,
-            justify_content: JustifyContent::Center,
-            align_items: AlignItems::Center,
-            ..default()
-        })
-        .with_children(|parent| {
-            text1 = parent
-                .spawn((
-                    TextEditable {
-                        filter_out: ignore,
-                        filter_in: allow,
-                        max_length,
-                        placeholder: String::from("Placeholder"),
-                        ..default()
-                    },
-                    TextEditFocus,
-                    Text::new(TEXT_1),
-                ))
-                .observe(get_text)
-                .id();
+    // Replacing a selection frees its length
+    send_key(app.world_mut(), KeyCode::ShiftLeft, Key::Shift);
+    app.update();
+    send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    app.update();
+    release_key(app.world_mut(), KeyCode::ShiftLeft, Key::Shift);
+    app.update();
+    send_ime_commit(app.world_mut(), "xyz");
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1ax|");
+}
 
-            text2 = parent
-                .spawn((TextEditable::default(), Interaction::None, Text::new(TEXT_2)))
-                .id();
-        });
+#[test]
+fn ime_preedit() {
+    let (mut app, text1_e, _) = setup_with_text("ab", vec![], vec![], 0);
+    let ime_enabled = |app: &mut App| {
+        app.world_mut()
+            .query::<&Window>()
+            .single(app.world())
+            .unwrap()
+            .ime_enabled
+    };
+    app.update();
+    assert!(ime_enabled(&mut app));
 
-    (app, text1, text2)
+    // The composed text replaces the selection in the displayed text only
+    send_key(app.world_mut(), KeyCode::ShiftLeft, Key::Shift);
+    app.update();
+    send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    app.update();
+    release_key(app.world_mut(), KeyCode::ShiftLeft, Key::Shift);
+    app.update();
+    send_ime(app.world_mut(), |window| Ime::Preedit {
+        window,
+        value: "にほ".into(),
+        cursor: Some((6, 6)),
+    });
+    app.update();
+    assert_eq!(app.world().get::<Text>(text1_e).unwrap().0, "aにほ");
+    assert_eq!(app.world().get::<TextEditBuffer>(text1_e).unwrap().value, "ab");
+
+    send_ime_commit(app.world_mut(), "日本");
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "a日本|");
+
+    send_key(app.world_mut(), KeyCode::Escape, Key::Escape);
+    app.update();
+    assert!(!ime_enabled(&mut app));
 }
 
-fn get_text(trigger: On<TextEdited>) {
-    info!("{}", trigger.text);
+#[test]
+fn ime_preedit_cancel() {
+    let (mut app, text1_e, _) = setup_with_text("ab", vec![], vec![], 0);
+    app.world_mut().resource_mut::<TextEditConfig>().blur_policy = BlurPolicy::Revert;
+    app.update();
+    send_ime(app.world_mut(), |window| Ime::Preedit {
+        window,
+        value: "にほ".into(),
+        cursor: Some((6, 6)),
+    });
+    app.update();
+
+    // Enter and Escape confirm or cancel the composition, not the edit
+    send_key(app.world_mut(), KeyCode::Enter, Key::Enter);
+    send_key(app.world_mut(), KeyCode::Escape, Key::Escape);
+    app.update();
+    assert!(app.world().get::<TextEditFocus>(text1_e).is_some());
+    assert_eq!(app.world().get::<Text>(text1_e).unwrap().0, "abにほ");
+
+    // The composition is dropped with the edit, it never reaches the value
+    click(app.world_mut());
+    app.update();
+    app.update();
+    assert!(app.world().get::<TextEditFocus>(text1_e).is_none());
+    assert_eq!(app.world().get::<TextEditBuffer>(text1_e).unwrap().value, "ab");
+    assert_eq!(app.world().get::<Text>(text1_e).unwrap().0, "ab");
 }
 
-fn send_key(world: &mut World, key_code: KeyCode, logical_key: Key) {
-    let mut window = world.query::<(Entity, &Window)>();
-    let Ok((window, _)) = window.single(world) else {
-        return;
+#[test]
+fn ime_position() {
+    let (mut app, text1_e, _) = setup_with_text("abc", vec![], vec![], 0);
+    add_layout_plugins(&mut app);
+    let ime_position = |app: &mut App| {
+        app.world_mut()
+            .query::<&Window>()
+            .single(app.world())
+            .unwrap()
+            .ime_position
     };
+    // The text is laid out after the plugin systems
+    app.update();
+    app.update();
+    let end = ime_position(&mut app);
+    assert!(end.x > 0.);
 
-    world.resource_mut::<Messages<KeyboardInput>>().write(KeyboardInput {
-        key_code,
-        logical_key,
-        state: ButtonState::Pressed,
+    // The candidate window follows the cursor
+    send_key(app.world_mut(), KeyCode::Home, Key::Home);
+    app.update();
+    let home = ime_position(&mut app);
+    assert!(home.x < end.x);
+    assert_eq!(home.y, end.y);
+
+    send_key(app.world_mut(), KeyCode::KeyD, Key::Character("d".into()));
+    app.update();
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "d|abc");
+    let typed = ime_position(&mut app);
+    assert!(home.x < typed.x && typed.x < end.x);
+}
+
+#[test]
+fn mask() {
+    let (mut app, text1_e, _) = setup_with_text("ab", vec![], vec![], 0);
+    {
+        let mut text_editable = app.world_mut().get_mut::<TextEditable>(text1_e).unwrap();
+        text_editable.mask = Some('*');
+        text_editable.reveal_last_char = true;
+    }
+    app.update();
+    assert_eq!(app.world().get::<Text>(text1_e).unwrap().0, "**");
+
+    send_key(app.world_mut(), KeyCode::KeyC, Key::Character("c".into()));
+    app.update();
+    assert_eq!(app.world().get::<Text>(text1_e).unwrap().0, "**c");
+    assert_eq!(app.world().get::<TextEditBuffer>(text1_e).unwrap().value, "abc");
+
+    // Masked again once the reveal time is over
+    app.world_mut().resource_mut::<TextEditConfig>().mask_reveal_duration = 0.;
+    app.update();
+    app.update();
+    assert_eq!(app.world().get::<Text>(text1_e).unwrap().0, "***");
+
+    // Passwords can be typed with IME, the composed text is masked too
+    let mut window = app.world_mut().query::<&Window>();
+    assert!(window.single(app.world()).unwrap().ime_enabled);
+    send_ime(app.world_mut(), |window| Ime::Preedit {
         window,
-        repeat: false,
-        text: None,
+        value: "é".into(),
+        cursor: Some((2, 2)),
     });
+    app.update();
+    assert_eq!(app.world().get::<Text>(text1_e).unwrap().0, "****");
+    send_ime_commit(app.world_mut(), "é");
+    app.update();
+    assert_eq!(app.world().get::<TextEditBuffer>(text1_e).unwrap().value, "abcé");
+}
+
+#[test]
+fn mask_cancel() {
+    let (mut app, text1_e, _) = setup_with_text("abcd", vec![], vec![], 0);
+    {
+        let mut text_editable = app.world_mut().get_mut::<TextEditable>(text1_e).unwrap();
+        text_editable.mask = Some('*');
+        text_editable.reveal_last_char = true;
+    }
+    app.update();
+    let mut cursor = app.world().resource::<Messages<TextChanged>>().get_cursor();
+
+    // Type in the middle, then cancel while the typed character is revealed
+    send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    send_key(app.world_mut(), KeyCode::KeyX, Key::Character("X".into()));
+    app.update();
+    assert_eq!(app.world().get::<Text>(text1_e).unwrap().0, "**X**");
+    send_key(app.world_mut(), KeyCode::Escape, Key::Escape);
+    app.update();
+    app.update();
+    assert_eq!(app.world().get::<TextEditBuffer>(text1_e).unwrap().value, "abcd");
+    assert_eq!(app.world().get::<Text>(text1_e).unwrap().0, "****");
+
+    let messages = app.world().resource::<Messages<TextChanged>>();
+    let causes: Vec<_> = cursor.read(messages).map(|e| e.cause).collect();
+    assert_eq!(causes, vec![TextChangeCause::Typed, TextChangeCause::Reverted]);
+}
+
+#[test]
+fn length_unit() {
+    let (mut app, text1_e, _) = setup_with_text("日本", vec![], vec![], 8);
+    app.world_mut().get_mut::<TextEditable>(text1_e).unwrap().length_unit = LengthUnit::Bytes;
+
+    // 3 bytes each
+    send_ime_commit(app.world_mut(), "語語");
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "日本|");
+    let mut cursor = app.world().resource::<Messages<TextInputRejected>>().get_cursor();
+    let rejected = cursor
+        .read(app.world().resource::<Messages<TextInputRejected>>())
+        .next()
+        .unwrap();
+    assert_eq!(rejected.text, "語語");
+    assert_eq!(rejected.limit, TextLimit::MaxLength);
+
+    app.world_mut().get_mut::<TextEditable>(text1_e).unwrap().length_unit = LengthUnit::Chars;
+    send_ime_commit(app.world_mut(), "語語");
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "日本語語|");
+}
+
+#[test]
+fn max_lines() {
+    let (mut app, text1_e, _) = setup(vec![], vec![], 0);
+    {
+        let mut text_editable = app.world_mut().get_mut::<TextEditable>(text1_e).unwrap();
+        text_editable.multiline = true;
+        text_editable.max_lines = 2;
+    }
+
+    send_key(app.world_mut(), KeyCode::Enter, Key::Enter);
+    send_key(app.world_mut(), KeyCode::KeyA, Key::Character("a".into()));
+    send_key(app.world_mut(), KeyCode::Enter, Key::Enter);
+    send_key(app.world_mut(), KeyCode::KeyB, Key::Character("b".into()));
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1\nab|");
+
+    let mut cursor = app.world().resource::<Messages<TextInputRejected>>().get_cursor();
+    let rejected = cursor
+        .read(app.world().resource::<Messages<TextInputRejected>>())
+        .next()
+        .unwrap();
+    assert_eq!(rejected.text, "\n");
+    assert_eq!(rejected.limit, TextLimit::MaxLines);
+}
+
+#[test]
+fn unicode_arrow_backspace() {
+    let (mut app, text1_e, _) = setup_with_text("Việt 日本👍🏽", vec![], vec![], 0);
+
+    // The emoji with skin tone modifier is a single grapheme
+    send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Việt 日本|👍🏽");
+
+    send_key(app.world_mut(), KeyCode::Backspace, Key::Backspace);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Việt 日|👍🏽");
+
+    send_key(app.world_mut(), KeyCode::Home, Key::Home);
+    send_key(app.world_mut(), KeyCode::ArrowRight, Key::ArrowRight);
+    send_key(app.world_mut(), KeyCode::ArrowRight, Key::ArrowRight);
+    send_key(app.world_mut(), KeyCode::ArrowRight, Key::ArrowRight);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Việ|t 日👍🏽");
+}
+
+#[test]
+fn unicode_delete() {
+    // "e" followed by a combining acute accent
+    let (mut app, text1_e, _) = setup_with_text("ae\u{301}日", vec![], vec![], 0);
+
+    send_key(app.world_mut(), KeyCode::Home, Key::Home);
+    send_key(app.world_mut(), KeyCode::ArrowRight, Key::ArrowRight);
+    send_key(app.world_mut(), KeyCode::Delete, Key::Delete);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "a|日");
+
+    send_key(app.world_mut(), KeyCode::Delete, Key::Delete);
+    send_key(app.world_mut(), KeyCode::Delete, Key::Delete);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "a|");
+}
+
+#[test]
+fn unicode_input() {
+    let (mut app, text1_e, _) = setup_with_text("", vec![], vec![], 4);
+
+    send_key(app.world_mut(), KeyCode::KeyE, Key::Character("é".into()));
+    send_key(app.world_mut(), KeyCode::KeyA, Key::Character("日".into()));
+    send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    send_key(app.world_mut(), KeyCode::KeyB, Key::Character("ệ".into()));
+    send_key(app.world_mut(), KeyCode::KeyC, Key::Character("👍🏽".into()));
+    // Over max length
+    send_key(app.world_mut(), KeyCode::KeyD, Key::Character("本".into()));
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "éệ👍🏽|日");
+}
+
+#[test]
+fn unicode_cursor_from_code() {
+    let (mut app, text1_e, _) = setup_with_text("a日👍🏽", vec![], vec![], 0);
+    app.update();
+
+    // Positions inside a grapheme or past the end are moved back to a boundary
+    let mut buffer = app.world_mut().get_mut::<TextEditBuffer>(text1_e).unwrap();
+    buffer.cursor = 2;
+    buffer.anchor = Some(100);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "a|日👍🏽");
+    let buffer = app.world().get::<TextEditBuffer>(text1_e).unwrap();
+    assert_eq!(buffer.anchor, Some("a日👍🏽".len()));
+
+    let mut buffer = app.world_mut().get_mut::<TextEditBuffer>(text1_e).unwrap();
+    buffer.cursor = 8;
+    buffer.anchor = None;
+    send_key(app.world_mut(), KeyCode::KeyB, Key::Character("b".into()));
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "a日b|👍🏽");
+}
+
+#[test]
+fn unicode_cursor_before_update() {
+    let (mut app, text1_e, _) = setup_with_text("a日👍🏽", vec![], vec![], 0);
+    app.update();
+
+    // Positions set from code are used before the plugin systems move them back to a boundary
+    let mut buffer = app.world_mut().get_mut::<TextEditBuffer>(text1_e).unwrap();
+    buffer.cursor = 2;
+    buffer.anchor = Some(5);
+    assert_eq!(buffer.selected_text(), "日");
+    app.world_mut().commands().entity(text1_e).insert_text_at_cursor("b");
+    app.world_mut().flush();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "ab|👍🏽");
+
+    let mut buffer = app.world_mut().get_mut::<TextEditBuffer>(text1_e).unwrap();
+    buffer.cursor = 100;
+    buffer.anchor = Some(100);
+    press(app.world_mut(), text1_e);
+    assert_eq!(text_with_cursor(app.world(), text1_e), "ab👍🏽|");
+}
+
+#[test]
+fn shift_arrow_select() {
+    let (mut app, text1_e, _) = setup(vec![], vec![], 0);
+
+    send_key(app.world_mut(), KeyCode::ShiftLeft, Key::Shift);
+    app.update();
+    send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    app.update();
+    release_key(app.world_mut(), KeyCode::ShiftLeft, Key::Shift);
+    app.update();
+    let buffer = app.world().get::<TextEditBuffer>(text1_e).unwrap();
+    assert_eq!(buffer.selection(), Some(11..13));
+    assert_eq!(buffer.selected_text(), "n1");
+
+    // Typing replaces the selection
+    send_key(app.world_mut(), KeyCode::KeyX, Key::Character("x".into()));
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Sectiox|");
+    let buffer = app.world().get::<TextEditBuffer>(text1_e).unwrap();
+    assert_eq!(buffer.selection(), None);
+}
+
+#[test]
+fn arrow_collapse_selection() {
+    let (mut app, text1_e, _) = setup(vec![], vec![], 0);
+
+    send_key(app.world_mut(), KeyCode::ShiftLeft, Key::Shift);
+    app.update();
+    send_key(app.world_mut(), KeyCode::Home, Key::Home);
+    app.update();
+    release_key(app.world_mut(), KeyCode::ShiftLeft, Key::Shift);
+    app.update();
+    let buffer = app.world().get::<TextEditBuffer>(text1_e).unwrap();
+    assert_eq!(buffer.selected_text(), TEXT_1);
+
+    send_key(app.world_mut(), KeyCode::ArrowRight, Key::ArrowRight);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1|");
+    let buffer = app.world().get::<TextEditBuffer>(text1_e).unwrap();
+    assert_eq!(buffer.selection(), None);
+}
+
+#[test]
+fn select_all_delete() {
+    let (mut app, text1_e, _) = setup(vec![], vec![], 0);
+
+    send_key(app.world_mut(), KeyCode::ControlLeft, Key::Control);
+    app.update();
+    send_key(app.world_mut(), KeyCode::KeyA, Key::Character("a".into()));
+    app.update();
+    release_key(app.world_mut(), KeyCode::ControlLeft, Key::Control);
+    app.update();
+    let buffer = app.world().get::<TextEditBuffer>(text1_e).unwrap();
+    assert_eq!(buffer.selected_text(), TEXT_1);
+
+    send_key(app.world_mut(), KeyCode::Delete, Key::Delete);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "|");
+}
+
+#[cfg(feature = "clipboard")]
+#[test]
+fn copy_cut() {
+    let (mut app, text1_e, _) = setup(vec![], vec![], 0);
+
+    // Select "Section1"
+    send_key(app.world_mut(), KeyCode::ShiftLeft, Key::Shift);
+    app.update();
+    for _ in 0..8 {
+        send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    }
+    app.update();
+    release_key(app.world_mut(), KeyCode::ShiftLeft, Key::Shift);
+    app.update();
+
+    send_key(app.world_mut(), KeyCode::ControlLeft, Key::Control);
+    app.update();
+    send_key(app.world_mut(), KeyCode::KeyC, Key::Character("c".into()));
+    app.update();
+    let buffer = app.world().get::<TextEditBuffer>(text1_e).unwrap();
+    assert_eq!(buffer.value, TEXT_1);
+    assert_eq!(buffer.selected_text(), "Section1");
+
+    // Cut works without a system clipboard too
+    send_key(app.world_mut(), KeyCode::KeyX, Key::Character("x".into()));
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_|");
+
+    // The whole value is cut if nothing is selected
+    send_key(app.world_mut(), KeyCode::KeyX, Key::Character("x".into()));
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "|");
+}
+
+#[cfg(feature = "clipboard")]
+#[test]
+fn copy_cut_blocked() {
+    let (mut app, text1_e, _) = setup_with_text("ab", vec![], vec![], 0);
+    app.world_mut().get_mut::<TextEditable>(text1_e).unwrap().mask = Some('*');
+    app.update();
+
+    // The value of a masked text can't be cut
+    send_key(app.world_mut(), KeyCode::ControlLeft, Key::Control);
+    app.update();
+    send_key(app.world_mut(), KeyCode::KeyA, Key::Character("a".into()));
+    send_key(app.world_mut(), KeyCode::KeyX, Key::Character("x".into()));
+    app.update();
+    let buffer = app.world().get::<TextEditBuffer>(text1_e).unwrap();
+    assert_eq!(buffer.value, "ab");
+    assert_eq!(buffer.selected_text(), "ab");
+
+    // Nor the value of a read-only text
+    {
+        let mut text_editable = app.world_mut().get_mut::<TextEditable>(text1_e).unwrap();
+        text_editable.mask = None;
+        text_editable.read_only = true;
+    }
+    send_key(app.world_mut(), KeyCode::KeyX, Key::Character("x".into()));
+    app.update();
+    let buffer = app.world().get::<TextEditBuffer>(text1_e).unwrap();
+    assert_eq!(buffer.value, "ab");
+    assert_eq!(buffer.selected_text(), "ab");
+}
+
+#[cfg(not(feature = "clipboard"))]
+#[test]
+fn copy_without_clipboard() {
+    let (mut app, text1_e, _) = setup(vec![], vec![], 0);
+
+    send_key(app.world_mut(), KeyCode::ControlLeft, Key::Control);
+    app.update();
+    send_key(app.world_mut(), KeyCode::KeyC, Key::Character("c".into()));
+    send_key(app.world_mut(), KeyCode::KeyX, Key::Character("x".into()));
+    send_key(app.world_mut(), KeyCode::KeyS, Key::Character("s".into()));
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1|");
+}
+
+#[test]
+fn undo_redo() {
+    let (mut app, text1_e, _) = setup(vec![], vec![], 0);
+
+    // Typed characters are merged into one undo step
+    send_key(app.world_mut(), KeyCode::KeyA, Key::Character("a".into()));
+    send_key(app.world_mut(), KeyCode::KeyB, Key::Character("b".into()));
+    send_key(app.world_mut(), KeyCode::KeyC, Key::Character("c".into()));
+    send_key(app.world_mut(), KeyCode::Backspace, Key::Backspace);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1ab|");
+
+    send_key(app.world_mut(), KeyCode::ControlLeft, Key::Control);
+    app.update();
+    send_key(app.world_mut(), KeyCode::KeyZ, Key::Character("z".into()));
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1abc|");
+
+    send_key(app.world_mut(), KeyCode::KeyZ, Key::Character("z".into()));
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1|");
+
+    send_key(app.world_mut(), KeyCode::KeyY, Key::Character("y".into()));
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1abc|");
+
+    send_key(app.world_mut(), KeyCode::ShiftLeft, Key::Shift);
+    app.update();
+    send_key(app.world_mut(), KeyCode::KeyZ, Key::Character("Z".into()));
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1ab|");
+    assert_eq!(app.world().get::<Text>(text1_e).unwrap().0, "Text_Section1ab");
+}
+
+#[test]
+fn undo_after_press() {
+    let (mut app, text1_e, _) = setup_with_text("xy", vec![], vec![], 0);
+
+    send_key(app.world_mut(), KeyCode::Home, Key::Home);
+    send_key(app.world_mut(), KeyCode::KeyA, Key::Character("a".into()));
+    app.update();
+    // Without a layout, a press places the cursor at the end
+    press(app.world_mut(), text1_e);
+    send_key(app.world_mut(), KeyCode::KeyB, Key::Character("b".into()));
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "axyb|");
+
+    // Typing after a press is a new undo step
+    send_key(app.world_mut(), KeyCode::ControlLeft, Key::Control);
+    app.update();
+    send_key(app.world_mut(), KeyCode::KeyZ, Key::Character("z".into()));
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "axy|");
+    send_key(app.world_mut(), KeyCode::KeyZ, Key::Character("z".into()));
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "|xy");
+}
+
+#[test]
+fn undo_api() {
+    let (mut app, text1_e, _) = setup(vec![], vec![], 0);
+
+    app.world_mut().get_mut::<TextEditHistory>(text1_e).unwrap().max_depth = 1;
+    send_key(app.world_mut(), KeyCode::Backspace, Key::Backspace);
+    app.update();
+    send_key(app.world_mut(), KeyCode::Backspace, Key::Backspace);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Sectio|");
+
+    let mut query = app.world_mut().query::<(&mut TextEditHistory, &mut TextEditBuffer)>();
+    let (mut history, mut buffer) = query.get_mut(app.world_mut(), text1_e).unwrap();
+    assert!(history.undo(&mut buffer));
+    // Only one step is kept
+    assert!(!history.undo(&mut buffer));
+    assert!(history.can_redo());
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section|");
+}
+
+#[test]
+fn word_navigation() {
+    let (mut app, text1_e, _) = setup_with_text("xin chào, thế giới", vec![], vec![], 0);
+
+    send_key(app.world_mut(), KeyCode::ControlLeft, Key::Control);
+    app.update();
+    send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "xin |chào, thế giới");
+
+    send_key(app.world_mut(), KeyCode::ArrowRight, Key::ArrowRight);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "xin chào|, thế giới");
+}
+
+#[test]
+fn word_deletion() {
+    let (mut app, text1_e, _) = setup_with_text("xin chào, thế giới", vec![], vec![], 0);
+
+    send_key(app.world_mut(), KeyCode::ControlLeft, Key::Control);
+    app.update();
+    send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    send_key(app.world_mut(), KeyCode::ArrowRight, Key::ArrowRight);
+    send_key(app.world_mut(), KeyCode::Backspace, Key::Backspace);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "xin chào, | giới");
+
+    send_key(app.world_mut(), KeyCode::Delete, Key::Delete);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "xin chào, |");
+}
+
+#[test]
+fn word_deletion_filtered() {
+    let (mut app, text1_e, _) = setup_with_text("3.14 15", vec![], vec!["[0-9.]".into()], 0);
+
+    send_key(app.world_mut(), KeyCode::ControlLeft, Key::Control);
+    app.update();
+    send_key(app.world_mut(), KeyCode::Backspace, Key::Backspace);
+    send_key(app.world_mut(), KeyCode::Backspace, Key::Backspace);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "|");
+}
+
+#[test]
+fn multiline_enter() {
+    let (mut app, text1_e, _) = setup_with_text("abc", vec![], vec![], 0);
+    app.world_mut().get_mut::<TextEditable>(text1_e).unwrap().multiline = true;
+
+    send_key(app.world_mut(), KeyCode::Enter, Key::Enter);
+    send_key(app.world_mut(), KeyCode::KeyD, Key::Character("d".into()));
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "abc\nd|");
+    assert!(app.world().get::<TextEditFocus>(text1_e).is_some());
+
+    // Ctrl+Enter commits
+    send_key(app.world_mut(), KeyCode::ControlLeft, Key::Control);
+    app.update();
+    send_key(app.world_mut(), KeyCode::Enter, Key::Enter);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "abc\nd|");
+    assert!(app.world().get::<TextEditFocus>(text1_e).is_none());
+}
+
+#[test]
+fn multiline_up_down() {
+    let (mut app, text1_e, _) = setup_with_text("chào bạn\nxin\nthế giới", vec![], vec![], 0);
+    app.world_mut().get_mut::<TextEditable>(text1_e).unwrap().multiline = true;
+
+    // The column is kept through the shorter line
+    send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    send_key(app.world_mut(), KeyCode::ArrowUp, Key::ArrowUp);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "chào bạn\nxin|\nthế giới");
+
+    send_key(app.world_mut(), KeyCode::ArrowUp, Key::ArrowUp);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "chào b|ạn\nxin\nthế giới");
+
+    send_key(app.world_mut(), KeyCode::ArrowDown, Key::ArrowDown);
+    send_key(app.world_mut(), KeyCode::ArrowDown, Key::ArrowDown);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "chào bạn\nxin\nthế gi|ới");
+
+    send_key(app.world_mut(), KeyCode::ArrowDown, Key::ArrowDown);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "chào bạn\nxin\nthế giới|");
+}
+
+#[test]
+fn multiline_home_end() {
+    let (mut app, text1_e, _) = setup_with_text("abc\ndef\nghi", vec![], vec![], 0);
+    app.world_mut().get_mut::<TextEditable>(text1_e).unwrap().multiline = true;
+
+    send_key(app.world_mut(), KeyCode::ArrowUp, Key::ArrowUp);
+    send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    send_key(app.world_mut(), KeyCode::Home, Key::Home);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "abc\n|def\nghi");
+
+    send_key(app.world_mut(), KeyCode::End, Key::End);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "abc\ndef|\nghi");
+
+    send_key(app.world_mut(), KeyCode::ControlLeft, Key::Control);
+    app.update();
+    send_key(app.world_mut(), KeyCode::Home, Key::Home);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "|abc\ndef\nghi");
+}
+
+#[test]
+fn text_changed() {
+    let (mut app, text1_e, _) = setup_with_text("ab", vec![], vec![], 0);
+    app.update();
+    let mut cursor = app.world().resource::<Messages<TextChanged>>().get_cursor();
+
+    send_key(app.world_mut(), KeyCode::KeyC, Key::Character("c".into()));
+    send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    send_key(app.world_mut(), KeyCode::Backspace, Key::Backspace);
+    app.update();
+    app.world_mut()
+        .get_mut::<TextEditBuffer>(text1_e)
+        .unwrap()
+        .set_value("xyz");
+    app.update();
+
+    let messages = app.world().resource::<Messages<TextChanged>>();
+    let changes: Vec<_> = cursor
+        .read(messages)
+        .map(|e| (e.old_text.as_str(), e.text.as_str(), e.cause))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            ("ab", "abc", TextChangeCause::Typed),
+            ("abc", "ac", TextChangeCause::Deleted),
+            ("ac", "xyz", TextChangeCause::Programmatic),
+        ]
+    );
+}
+
+#[test]
+fn escape_cancel() {
+    let (mut app, text1_e, _) = setup(vec![], vec![], 0);
+    app.update();
+    let mut cursor = app.world().resource::<Messages<TextEdited>>().get_cursor();
+
+    send_key(app.world_mut(), KeyCode::KeyA, Key::Character("a".into()));
+    app.update();
+    send_key(app.world_mut(), KeyCode::Escape, Key::Escape);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1|");
+    assert!(app.world().get::<TextEditFocus>(text1_e).is_none());
+
+    let messages = app.world().resource::<Messages<TextEdited>>();
+    let edited: Vec<_> = cursor.read(messages).map(|e| (e.text.as_str(), e.reason)).collect();
+    assert_eq!(edited, vec![("Text_Section1", TextEditEndReason::Cancel)]);
+}
+
+#[test]
+fn enter_submit() {
+    let (mut app, text1_e, _) = setup(vec![], vec![], 0);
+    app.update();
+    let mut cursor = app.world().resource::<Messages<TextEdited>>().get_cursor();
+
+    send_key(app.world_mut(), KeyCode::KeyA, Key::Character("a".into()));
+    app.update();
+    send_key(app.world_mut(), KeyCode::Enter, Key::Enter);
+    app.update();
+
+    let messages = app.world().resource::<Messages<TextEdited>>();
+    let edited: Vec<_> = cursor.read(messages).map(|e| (e.text.as_str(), e.reason)).collect();
+    assert_eq!(edited, vec![("Text_Section1a", TextEditEndReason::Submit)]);
+    assert!(app.world().get::<TextEditFocus>(text1_e).is_none());
+}
+
+#[test]
+fn blur_policy() {
+    let (mut app, text1_e, _) = setup(vec![], vec![], 0);
+    app.world_mut().resource_mut::<TextEditConfig>().blur_policy = BlurPolicy::Revert;
+    app.update();
+
+    send_key(app.world_mut(), KeyCode::KeyA, Key::Character("a".into()));
+    app.update();
+    click(app.world_mut());
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "Text_Section1|");
+    assert!(app.world().get::<TextEditFocus>(text1_e).is_none());
+}
+
+#[test]
+fn read_only() {
+    let (mut app, text1_e, _) = setup_with_text("ab", vec![], vec![], 0);
+    app.world_mut().get_mut::<TextEditable>(text1_e).unwrap().read_only = true;
+
+    send_key(app.world_mut(), KeyCode::KeyC, Key::Character("c".into()));
+    send_key(app.world_mut(), KeyCode::Backspace, Key::Backspace);
+    send_key(app.world_mut(), KeyCode::ArrowLeft, Key::ArrowLeft);
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text1_e), "a|b");
+    assert!(app.world().get::<TextEditFocus>(text1_e).is_some());
+}
+
+#[test]
+fn disabled() {
+    let (mut app, text1_e, _) = setup(vec![], vec![], 0);
+    let alpha = app.world().get::<TextColor>(text1_e).unwrap().0.alpha();
+
+    // A focused text loses focus when it is disabled
+    app.world_mut().get_mut::<TextEditable>(text1_e).unwrap().disabled = true;
+    app.update();
+    assert!(app.world().get::<TextEditFocus>(text1_e).is_none());
+    assert_eq!(app.world().get::<TextColor>(text1_e).unwrap().0.alpha(), alpha * 0.4);
+
+    app.world_mut().entity_mut(text1_e).insert(Interaction::Pressed);
+    app.update();
+    assert!(app.world().get::<TextEditFocus>(text1_e).is_none());
+
+    app.world_mut().get_mut::<TextEditable>(text1_e).unwrap().disabled = false;
+    app.update();
+    assert_eq!(app.world().get::<TextColor>(text1_e).unwrap().0.alpha(), alpha);
+}
+
+#[test]
+fn tab_focus() {
+    let (mut app, text1_e, text2_e) = setup(vec![], vec![], 0);
+
+    send_key(app.world_mut(), KeyCode::Tab, Key::Tab);
+    app.update();
+    assert!(app.world().get::<TextEditFocus>(text1_e).is_none());
+    assert!(app.world().get::<TextEditFocus>(text2_e).is_some());
+
+    // Focus wraps around
+    send_key(app.world_mut(), KeyCode::Tab, Key::Tab);
+    app.update();
+    assert!(app.world().get::<TextEditFocus>(text1_e).is_some());
+
+    send_key(app.world_mut(), KeyCode::ShiftLeft, Key::Shift);
+    send_key(app.world_mut(), KeyCode::Tab, Key::Tab);
+    app.update();
+    assert!(app.world().get::<TextEditFocus>(text2_e).is_some());
+    release_key(app.world_mut(), KeyCode::ShiftLeft, Key::Shift);
+    app.update();
+
+    // A text with negative index is skipped
+    app.world_mut().entity_mut(text1_e).insert(TextTabIndex(-1));
+    send_key(app.world_mut(), KeyCode::Tab, Key::Tab);
+    app.update();
+    assert!(app.world().get::<TextEditFocus>(text2_e).is_some());
+    assert!(app.world().get::<TextEditFocus>(text1_e).is_none());
+}
+
+#[test]
+fn commands() {
+    let (mut app, text1_e, text2_e) = setup(vec![], vec![], 0);
+    app.update();
+    let mut focus_cursor = app.world().resource::<Messages<TextFocusChanged>>().get_cursor();
+    let mut changed_cursor = app.world().resource::<Messages<TextChanged>>().get_cursor();
+
+    app.world_mut().commands().entity(text2_e).focus_text();
+    app.update();
+    assert!(app.world().get::<TextEditFocus>(text1_e).is_none());
+    assert!(app.world().get::<TextEditFocus>(text2_e).is_some());
+    let messages = app.world().resource::<Messages<TextFocusChanged>>();
+    assert!(matches!(
+        focus_cursor.read(messages).collect::<Vec<_>>()[..],
+        [TextFocusChanged::Show(_)]
+    ));
+
+    app.world_mut()
+        .commands()
+        .entity(text2_e)
+        .set_text_value("abc")
+        .select_all();
+    app.update();
+    let buffer = app.world().get::<TextEditBuffer>(text2_e).unwrap();
+    assert_eq!(buffer.selected_text(), "abc");
+
+    app.world_mut().commands().entity(text2_e).insert_text_at_cursor("d");
+    app.update();
+    assert_eq!(text_with_cursor(app.world(), text2_e), "d|");
+
+    let messages = app.world().resource::<Messages<TextChanged>>();
+    let changes: Vec<_> = changed_cursor
+        .read(messages)
+        .map(|e| (e.old_text.as_str(), e.text.as_str(), e.cause))
+        .collect();
+    assert_eq!(
+        changes,
+        vec![
+            (TEXT_2, "abc", TextChangeCause::Programmatic),
+            ("abc", "d", TextChangeCause::Programmatic),
+        ]
+    );
+
+    // Changes from code can be undone
+    send_key(app.world_mut(), KeyCode::ControlLeft, Key::Control);
+    send_key(app.world_mut(), KeyCode::KeyZ, Key::Character("z".into()));
+    app.update();
+    release_key(app.world_mut(), KeyCode::ControlLeft, Key::Control);
+    assert_eq!(app.world().get::<TextEditBuffer>(text2_e).unwrap().value, "abc");
+
+    app.world_mut().commands().entity(text2_e).blur_text();
+    app.update();
+    assert!(app.world().get::<TextEditFocus>(text2_e).is_none());
+}
+
+#[test]
+fn spawned_buffer() {
+    let (mut app, _, _) = setup(vec![], vec![], 0);
+    let spawned_e = app
+        .world_mut()
+        .spawn((TextEditable::default(), TextEditBuffer::new("Spawned"), Text::default()))
+        .id();
+    let set_e = app
+        .world_mut()
+        .spawn((TextEditable::default(), TextEditBuffer::new("Spawned"), Text::default()))
+        .id();
+    app.world_mut().commands().entity(set_e).set_text_value("Set");
+    app.update();
+
+    // The spawned buffer takes precedence over `Text`
+    assert_eq!(app.world().get::<Text>(spawned_e).unwrap().0, "Spawned");
+    assert_eq!(text_with_cursor(app.world(), spawned_e), "Spawned|");
+    assert_eq!(app.world().get::<Text>(set_e).unwrap().0, "Set");
+}
+
+#[test]
+fn multiline_line_break() {
+    let (mut app, text1_e, _) = setup(vec![], vec![], 0);
+    app.update();
+    assert_eq!(
+        app.world().get::<TextLayout>(text1_e).unwrap().linebreak,
+        LineBreak::NoWrap
+    );
+
+    app.world_mut().get_mut::<TextEditable>(text1_e).unwrap().multiline = true;
+    app.update();
+    assert_eq!(
+        app.world().get::<TextLayout>(text1_e).unwrap().linebreak,
+        LineBreak::WordBoundary
+    );
+
+    app.world_mut().get_mut::<TextEditable>(text1_e).unwrap().multiline = false;
+    app.update();
+    assert_eq!(
+        app.world().get::<TextLayout>(text1_e).unwrap().linebreak,
+        LineBreak::NoWrap
+    );
+}
+
+#[test]
+fn config_default() {
+    let config = TextEditConfig {
+        blink: true,
+        ..default()
+    };
+    assert_eq!(config.cursor_width, TextEditConfig::new().cursor_width);
+    assert_eq!(config.disabled_alpha, 0.4);
+    assert_eq!(config.selection_color, TextEditConfig::new().selection_color);
+}
+
+#[test]
+fn validation() {
+    let (mut app, text1_e, _) = setup_with_text("1.5", vec![], vec![], 0);
+    app.world_mut().entity_mut(text1_e).insert(
+        TextValidator::new()
+            .pattern(r"[0-9]+(\.[0-9]*)?", "Not a number")
+            .block_commit(true),
+    );
+    app.update();
+    assert_eq!(app.world().get::<TextValidation>(text1_e), Some(&TextValidation::Valid));
+
+    send_key(app.world_mut(), KeyCode::Period, Key::Character(".".into()));
+    app.update();
+    assert_eq!(
+        app.world().get::<TextValidation>(text1_e),
+        Some(&TextValidation::Invalid("Not a number".into()))
+    );
+
+    // Invalid value can't be submitted, the virtual keyboard stays to fix it
+    let mut focus_cursor = app.world().resource::<Messages<TextFocusChanged>>().get_cursor();
+    send_key(app.world_mut(), KeyCode::Enter, Key::Enter);
+    app.update();
+    assert!(app.world().get::<TextEditFocus>(text1_e).is_some());
+    let messages = app.world().resource::<Messages<TextFocusChanged>>();
+    assert!(focus_cursor.read(messages).next().is_none());
+
+    send_key(app.world_mut(), KeyCode::Backspace, Key::Backspace);
+    app.update();
+    assert_eq!(app.world().get::<TextValidation>(text1_e), Some(&TextValidation::Valid));
+    send_key(app.world_mut(), KeyCode::Enter, Key::Enter);
+    app.update();
+    assert!(app.world().get::<TextEditFocus>(text1_e).is_none());
+}
+
+fn setup(ignore: Vec<String>, allow: Vec<String>, max_length: usize) -> (App, Entity, Entity) {
+    setup_with_text(TEXT_1, ignore, allow, max_length)
+}
+
+fn setup_with_text(text: &str, ignore: Vec<String>, allow: Vec<String>, max_length: usize) -> (App, Entity, Entity) {
+    let mut app = App::new();
+    let mut text1 = Entity::PLACEHOLDER;
+    let mut text2 = Entity::PLACEHOLDER;
+
+    app.add_plugins((
+        WindowPlugin::default(),
+        InputPlugin,
+        TimePlugin,
+        TextEditPluginAnyState::any(),
+    ));
+
+    app.world_mut()
+        .spawn(Node {
+            width: Val::Percent(100.),
+            height: Val::Percent(100.),
+            flex_direction: FlexDirection::Column,
+            justify_content: JustifyContent::Center,
+            align_items: AlignItems::Center,
+            ..default()
+        })
+        .with_children(|parent| {
+            text1 = parent
+                .spawn((
+                    TextEditable {
+                        filter_out: ignore,
+                        filter_in: allow,
+                        max_length,
+                        placeholder: String::from("Placeholder"),
+                        ..default()
+                    },
+                    TextEditFocus,
+                    Text::new(text),
+                ))
+                .observe(get_text)
+                .id();
+
+            text2 = parent
+                .spawn((TextEditable::default(), Interaction::None, Text::new(TEXT_2)))
+                .id();
+        });
+
+    (app, text1, text2)
+}
+
+/// Add the plugins laying out `Text`, so that positions in the text are known.
+fn add_layout_plugins(app: &mut App) {
+    app.add_plugins((
+        TaskPoolPlugin::default(),
+        TransformPlugin,
+        AssetPlugin::default(),
+        ImagePlugin::default(),
+        TextureAtlasPlugin,
+        TextPlugin,
+        UiPlugin,
+        DefaultPickingPlugins,
+    ));
+}
+
+/// Buffer value with `|` inserted at cursor position.
+fn text_with_cursor(world: &World, e: Entity) -> String {
+    let buffer = world.get::<TextEditBuffer>(e).unwrap();
+    let mut text = buffer.value.clone();
+    text.insert(buffer.cursor, '|');
+    text
+}
+
+fn get_text(trigger: On<TextEdited>) {
+    info!("{}", trigger.text);
+}
+
+/// Press the text with the primary pointer button.
+fn press(world: &mut World, e: Entity) {
+    let mut window = world.query_filtered::<Entity, With<Window>>();
+    let window = window.single(world).unwrap();
+    let location = Location {
+        target: NormalizedRenderTarget::Window(WindowRef::Primary.normalize(Some(window)).unwrap()),
+        position: Vec2::ZERO,
+    };
+    let event = Press {
+        button: PointerButton::Primary,
+        hit: HitData::new(Entity::PLACEHOLDER, 0., None, None),
+        count: 1,
+    };
+    world.trigger(Pointer::new(PointerId::Mouse, location, event, e));
+}
+
+fn send_key(world: &mut World, key_code: KeyCode, logical_key: Key) {
+    send_key_state(world, key_code, logical_key, ButtonState::Pressed);
+}
+
+fn release_key(world: &mut World, key_code: KeyCode, logical_key: Key) {
+    send_key_state(world, key_code, logical_key, ButtonState::Released);
+}
+
+fn send_key_state(world: &mut World, key_code: KeyCode, logical_key: Key, state: ButtonState) {
+    let mut window = world.query::<(Entity, &Window)>();
+    let Ok((window, _)) = window.single(world) else {
+        return;
+    };
+
+    world.resource_mut::<Messages<KeyboardInput>>().write(KeyboardInput {
+        key_code,
+        logical_key,
+        state,
+        window,
+        repeat: false,
+        text: None,
+    });
+}
+
+fn send_ime(world: &mut World, ime: impl FnOnce(Entity) -> Ime) {
+    let mut window = world.query::<(Entity, &Window)>();
+    let Ok((window, _)) = window.single(world) else {
+        return;
+    };
+
+    world.resource_mut::<Messages<Ime>>().write(ime(window));
+}
+
+fn send_ime_commit(world: &mut World, value: &str) {
+    send_ime(world, |window| Ime::Commit {
+        window,
+        value: value.to_string(),
+    });
+}
+
+fn click(world: &mut World) {
+    let mut window = world.query::<(Entity, &Window)>();
+    let Ok((window, _)) = window.single(world) else {
+        return;
+    };
+
+    world
+        .resource_mut::<Messages<MouseButtonInput>>()
+        .write(MouseButtonInput {
+            button: MouseButton::Left,
+            state: ButtonState::Pressed,
+            window,
+        });
 }